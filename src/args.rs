@@ -1,11 +1,18 @@
 use clap::{App, Arg};
-use sqlit::ingest::{SepFinder, TsvFinder, SpacesFinder, CsvFinder};
+use std::sync::Arc;
+use sqlit::ingest::{SepFinder, TsvFinder, SpacesFinder, CsvFinder, LoadOptions, InputFormat, Encoding, Epoch, parse_table_path, parse_table_flag,
+                    parse_schema, parse_types};
+use sqlit::output::FORMATS;
 
 pub struct Config {
-    pub table_paths: Vec<String>,
+    /// every table to load at startup with the options it is read with
+    pub tables: Vec<(String, LoadOptions)>,
     pub parse_columns: bool,
-    pub separator: Box<dyn SepFinder>,
-    pub null_representation: String,
+    pub execute: Option<String>,
+    pub script_path: Option<String>,
+    pub format: String,
+    /// reload tables whose files changed before each query
+    pub watch: bool,
 }
 
 pub fn get() -> Config {
@@ -14,14 +21,50 @@ pub fn get() -> Config {
         .author("max grinchenko <frismo98@gmail.com>")
         .about("sql-ize your csvs from the command line")
         .arg(Arg::new("tables")
-            .required(true)
-            .about("tables that will be parsed at startup")
+            .about("tables that will be parsed at startup, - reads stdin (queries then come from -e or -f), more can be added with LOAD 'path'. \
+                    Options for a single file follow a colon, IE sales.tsv:sep=tab,null=NA,name=sales. \
+                    A comma in a value is written \\,")
             .index(1)
             .multiple(true))
+        .arg(Arg::new("table")
+            .about("a named table and its options, IE --table sales=sales.tsv,sep=tab,null=NA")
+            .long("table")
+            .takes_value(true)
+            .multiple_occurrences(true))
+        .arg(Arg::new("sqlite")
+            .about("a sqlite database whose tables are all loaded under their own names, IE --sqlite app.db")
+            .long("sqlite")
+            .takes_value(true)
+            .multiple_occurrences(true))
         .arg(Arg::new("column_help")
-            .about("enter column names manually at startup")
+            .about("enter column names, and optionally types, for each table at startup")
             .short('c')
             .long("columns"))
+        .arg(Arg::new("schema")
+            .about("column names and optional types for the tables, IE id:int,name,price:float")
+            .long("schema")
+            .takes_value(true))
+        .arg(Arg::new("types")
+            .about("types for columns by name that skip inference, IE zip:string,id:string")
+            .long("types")
+            .takes_value(true))
+        .arg(Arg::new("strict")
+            .about("fail to load a table when a value doesn't match its declared type or a line has the wrong number of \
+                    fields, instead of reading the value as null or skipping the line")
+            .long("strict"))
+        .arg(Arg::new("pad_missing")
+            .about("fill out lines with too few fields with nulls instead of skipping them")
+            .long("pad-missing"))
+        .arg(Arg::new("rejects")
+            .about("a file the lines skipped for having the wrong number of fields are added to, IE --rejects rejects.csv")
+            .long("rejects")
+            .takes_value(true))
+        .arg(Arg::new("no_header")
+            .about("the first line is data, columns are named c0, c1, ... unless given with --schema")
+            .long("no-header"))
+        .arg(Arg::new("csv")
+            .about("looks for a comma as the delimiter between columns, by default it is detected")
+            .long("csv"))
         .arg(Arg::new("tsv")
             .about("looks for a tab as the delimiter between columns")
             .short('t')
@@ -29,32 +72,151 @@ pub fn get() -> Config {
         .arg(Arg::new("spaces")
             .about("look for two + spaces as the delimiter between columns")
             .short('s')
-            .long("spaces")
-            .long("tsv"))
+            .long("spaces"))
+        .arg(Arg::new("encoding")
+            .about("what text files are encoded in, one of utf-8, latin-1 or windows-1252. By default it is detected")
+            .long("encoding")
+            .takes_value(true))
+        .arg(Arg::new("date_format")
+            .about("a date format tried before the built in ones, IE --date-format '%d.%m.%Y %H:%M'")
+            .long("date-format")
+            .takes_value(true)
+            .multiple_occurrences(true))
+        .arg(Arg::new("epoch")
+            .about("the unit of whole numbers in columns declared as dates, one of s, ms or auto which reads \
+                    numbers too large to be seconds as milliseconds")
+            .long("epoch")
+            .takes_value(true))
+        .arg(Arg::new("yes_no")
+            .about("read y/n, t/f, yes/no and 1/0 as booleans, as well as true and false")
+            .long("yes-no"))
+        .arg(Arg::new("formatted_numbers")
+            .about("read numbers written for people as numbers too, IE 1,234.5, $3.50 and 12%")
+            .long("formatted-numbers"))
+        .arg(Arg::new("decimal_comma")
+            .about("read formatted numbers with a decimal comma and their thousands grouped by points, IE 1.234,5")
+            .long("decimal-comma"))
         .arg(Arg::new("null_representation")
             .about("what the null representation is in the file")
             .short('n')
             .long("null")
             .default_value("null"))
+        .arg(Arg::new("execute")
+            .about("runs the given ;-separated queries and exits instead of starting the prompt")
+            .short('e')
+            .long("execute")
+            .takes_value(true))
+        .arg(Arg::new("script")
+            .about("runs the queries in the given file and exits instead of starting the prompt")
+            .short('f')
+            .long("file")
+            .takes_value(true)
+            .conflicts_with("execute"))
+        .arg(Arg::new("watch")
+            .about("before each query, reload the tables whose files changed since they were loaded. Only the new lines \
+                    of a file that was added to are read. A table can also be reloaded at the prompt with \\reload <table>")
+            .short('w')
+            .long("watch"))
+        .arg(Arg::new("format")
+            .about("how results are written, can be changed at the prompt with .mode <format>")
+            .long("format")
+            .possible_values(&FORMATS)
+            .default_value(FORMATS[0]))
         .get_matches();
 
-    let table_paths: Vec<_> = matches.values_of("tables").unwrap().map(|s| s.to_string()).collect();
     let parse_columns = !(matches.occurrences_of("column_help") > 0);
+    // without a flag the separator is detected per file
     let separator = if matches.occurrences_of("tsv") > 0 {
-            Box::new(TsvFinder{}) as Box<dyn SepFinder>
+            Some(Arc::new(TsvFinder{}) as Arc<dyn SepFinder>)
         } else if matches.occurrences_of("spaces") > 0 {
-            Box::new(SpacesFinder{}) as Box<dyn SepFinder>
+            Some(Arc::new(SpacesFinder{}) as Arc<dyn SepFinder>)
+        } else if matches.occurrences_of("csv") > 0 {
+            Some(Arc::new(CsvFinder{}) as Arc<dyn SepFinder>)
         } else {
-            Box::new(CsvFinder{}) as Box<dyn SepFinder>
+            None
         };
 
-    let null_representation = matches.value_of("null_representation").unwrap().to_string();
+    let null_representation = matches.value_of("null_representation").unwrap();
+
+    // the flags above are defaults that each table can override
+    let mut defaults = LoadOptions::new(separator, null_representation);
+
+    if matches.occurrences_of("no_header") > 0 {
+        defaults.header = Some(false);
+    }
+
+    defaults.strict = matches.occurrences_of("strict") > 0;
+    defaults.pad_missing = matches.occurrences_of("pad_missing") > 0;
+    defaults.rejects = matches.value_of("rejects").map(|path| path.to_string());
+
+    if let Some(name) = matches.value_of("encoding") {
+        defaults.encoding = Some(Encoding::from_name(name).unwrap_or_else(|| {
+            eprintln!("unknown encoding {}, expected utf-8, latin-1 or windows-1252", name);
+            std::process::exit(1);
+        }));
+    }
+
+    for format in matches.values_of("date_format").into_iter().flatten() {
+        defaults.formats.add_date(format).unwrap_or_else(|| {
+            eprintln!("{} is not a date format, IE %d.%m.%Y %H:%M", format);
+            std::process::exit(1);
+        });
+    }
+
+    if let Some(unit) = matches.value_of("epoch") {
+        defaults.formats.epoch = Epoch::from_name(unit).unwrap_or_else(|| {
+            eprintln!("unknown epoch unit {}, expected s, ms or auto", unit);
+            std::process::exit(1);
+        });
+    }
+
+    defaults.formats.yes_no = matches.occurrences_of("yes_no") > 0;
+    defaults.formats.formatted_numbers = matches.occurrences_of("formatted_numbers") > 0;
+
+    if matches.occurrences_of("decimal_comma") > 0 {
+        defaults.formats.formatted_numbers = true;
+        defaults.formats.decimal = ',';
+    }
+
+    if let Some(types) = matches.value_of("types") {
+        defaults.types = parse_types(types).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+    }
+
+    if let Some(schema) = matches.value_of("schema") {
+        defaults.columns = Some(parse_schema(schema).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }));
+    }
+
+    let paths = matches.values_of("tables").into_iter().flatten()
+        .map(|spec| parse_table_path(spec, &defaults));
+
+    let flags = matches.values_of("table").into_iter().flatten()
+        .map(|spec| parse_table_flag(spec, &defaults));
+
+    let databases = matches.values_of("sqlite").into_iter().flatten().map(|path| {
+        let mut options = defaults.clone();
+        options.format = Some(InputFormat::Sqlite);
+
+        Ok((path.to_string(), options))
+    });
+
+    let tables = paths.chain(flags).chain(databases).collect::<Result<Vec<_>, _>>().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
 
     Config {
-        table_paths,
+        tables,
         parse_columns,
-        separator,
-        null_representation
+        execute: matches.value_of("execute").map(|s| s.to_string()),
+        script_path: matches.value_of("script").map(|s| s.to_string()),
+        format: matches.value_of("format").unwrap().to_string(),
+        watch: matches.occurrences_of("watch") > 0,
     }
 
 }
@@ -1,5 +1,6 @@
-use crate::converters::{Converter, ToBool, ToDate, ToFloat, ToInt};
-use crate::table::Column;
+use crate::converters::{self, Converter, ToBool, ToDate, ToFloat, ToInt, ToYesNo, ToFormattedInt, ToFormattedFloat};
+use crate::table::{Column, ColumnType, DateTime};
+use crate::ingest::Formats;
 
 /// Converts raw values Vec<String> into a column
 ///
@@ -7,39 +8,185 @@ use crate::table::Column;
 ///
 /// raw_values -> a vector of Strings, from which we convert into columns
 /// null_as_string -> the string value of null, which will be checked in each converter
+///
+/// values are read plainly, so '$5' in a query stays a string
 pub fn build_column(raw_values: Vec<String>, null_as_string: &str) -> Column {
+    infer_column(&raw_values.into_iter().map(Some).collect::<Vec<_>>(), null_as_string, &Formats::plain())
+}
+
+fn booleans(formats: &Formats) -> Box<dyn Converter<bool>> {
+    if formats.yes_no { Box::new(ToYesNo{}) } else { Box::new(ToBool{}) }
+}
 
-    if let Some(converted_column) = convert_into_column(&raw_values, Box::new(ToBool{}), null_as_string) {
+fn ints(formats: &Formats) -> Box<dyn Converter<i64>> {
+    if formats.formatted_numbers { Box::new(ToFormattedInt { decimal: formats.decimal }) } else { Box::new(ToInt{}) }
+}
+
+fn floats(formats: &Formats) -> Box<dyn Converter<f64>> {
+    if formats.formatted_numbers { Box::new(ToFormattedFloat { decimal: formats.decimal }) } else { Box::new(ToFloat{}) }
+}
+
+/// declared is set for columns declared as dates, which read whole numbers as times since 1970
+fn dates(formats: &Formats, declared: bool) -> Box<dyn Converter<DateTime>> {
+    Box::new(ToDate::with_formats(formats, declared))
+}
+
+/// the first type every field converts to: boolean, date, int, float, or else string
+fn infer_column<S: AsRef<str>>(raw_values: &[Option<S>], null_as_string: &str, formats: &Formats) -> Column {
+
+    if let Some(converted_column) = convert_into_column(raw_values, booleans(formats), null_as_string) {
         converted_column
-    } else if let Some(converted_column) = convert_into_column(&raw_values, Box::new(ToDate::new()), null_as_string) {
+    } else if let Some(converted_column) = convert_into_column(raw_values, dates(formats, false), null_as_string) {
         converted_column
-    } else if let Some(converted_column) = convert_into_column(&raw_values,
-                                                               Box::new(ToInt{}), null_as_string) {
+    } else if let Some(converted_column) = convert_into_column(raw_values,
+                                                               ints(formats), null_as_string) {
         converted_column
-    } else if let Some(converted_column) = convert_into_column(&raw_values,
-                                                               Box::new(ToFloat{}), null_as_string) {
+    } else if let Some(converted_column) = convert_into_column(raw_values,
+                                                               floats(formats), null_as_string) {
         converted_column
     } else {
-        Column::Strings(raw_values.into_iter().map(|s| {
-            if &s == null_as_string {
-                None
-            } else {
-                Some(s)
-            }
-        }).collect())
+        strings(raw_values, null_as_string)
+    }
+}
+
+/// the fields as strings, the only place they are copied out of the text they were read from
+fn strings<S: AsRef<str>>(raw_values: &[Option<S>], null_as_string: &str) -> Column {
+    Column::Strings(raw_values.iter().map(|s| {
+        s.as_ref().map(|s| s.as_ref()).filter(|&s| s != null_as_string).map(str::to_string)
+    }).collect())
+}
+
+/// Converts fields into a column of the given type, fields that don't convert are null.
+/// Returns the column and how many fields did not convert.
+pub fn build_typed_column(raw_values: Vec<Option<String>>, null_as_string: &str, column_type: &ColumnType) -> (Column, usize) {
+    typed_column(&raw_values, null_as_string, column_type, &Formats::default())
+}
+
+fn typed_column<S: AsRef<str>>(raw_values: &[Option<S>], null_as_string: &str, column_type: &ColumnType, formats: &Formats) -> (Column, usize) {
+    match column_type {
+        ColumnType::Boolean => convert_or_null(raw_values, booleans(formats), null_as_string),
+        ColumnType::Date => convert_or_null(raw_values, dates(formats, true), null_as_string),
+        ColumnType::Int => convert_or_null(raw_values, ints(formats), null_as_string),
+        ColumnType::Float => convert_or_null(raw_values, floats(formats), null_as_string),
+        ColumnType::String => convert_or_null(raw_values, Box::new(converters::ToString{}), null_as_string),
+    }
+}
+
+/// Builds a column a chunk of fields at a time, so only one chunk of raw text is held at once.
+/// Each chunk is converted to the type found so far, a chunk that doesn't fit is inferred on its own
+/// and the column is promoted to hold both: ints become floats, any other mix becomes strings.
+/// Earlier chunks turned into strings are written as the values they were read as, IE 42 for 00042,
+/// see promoted.
+pub struct ColumnBuilder {
+    column: Option<Column>,
+    declared: Option<ColumnType>,
+    formats: Formats,
+    mismatches: usize,
+    promoted: bool,
+}
+
+impl ColumnBuilder {
+    /// a declared type skips inference, fields that don't convert are counted as mismatches.
+    /// formats says how fields are read as booleans, numbers and dates
+    pub fn with_formats(declared: Option<ColumnType>, formats: &Formats) -> Self {
+        ColumnBuilder { column: None, declared, formats: formats.clone(), mismatches: 0, promoted: false }
+    }
+
+    /// a column its source already gave a type, IE the numbers of a json file
+    pub fn from_column(column: Column) -> Self {
+        ColumnBuilder { column: Some(column), declared: None, formats: Formats::default(), mismatches: 0, promoted: false }
+    }
+
+    /// whether a later chunk turned values read from earlier ones into strings, a file is then read
+    /// again with the column declared as strings to keep those fields as written
+    pub fn promoted(&self) -> bool {
+        self.promoted
+    }
+
+    /// an unquoted empty field is read as None and is null in every column type, while a quoted
+    /// empty string is kept in string columns
+    pub fn push<S: AsRef<str>>(&mut self, raw_values: &[Option<S>], null_as_string: &str) {
+        let chunk = match (&self.column, &self.declared) {
+            (_, Some(column_type)) => {
+                let (chunk, mismatches) = typed_column(raw_values, null_as_string, column_type, &self.formats);
+                self.mismatches += mismatches;
+
+                chunk
+            },
+            (Some(column), None) if !column.is_all_null() => {
+                convert_as(raw_values, &column.type_(), null_as_string, &self.formats)
+                    .unwrap_or_else(|| infer_column(raw_values, null_as_string, &self.formats))
+            },
+            _ => infer_column(raw_values, null_as_string, &self.formats),
+        };
+
+        self.column = Some(match self.column.take() {
+            Some(column) => {
+                let was_values = column.type_() != ColumnType::String && !column.is_all_null();
+                let column = column.promote_and_extend(chunk);
+
+                self.promoted |= was_values && column.type_() == ColumnType::String;
+
+                column
+            },
+            None => chunk,
+        });
+    }
+
+    /// the column and how many fields did not match the declared type
+    pub fn finish(mut self, null_as_string: &str) -> (Column, usize) {
+        if self.column.is_none() {
+            self.push::<&str>(&[], null_as_string);
+        }
+
+        (self.column.unwrap(), self.mismatches)
     }
 }
 
-fn convert_into_column<T>(raw_values: &Vec<String>, mut converter: Box<dyn Converter<T>>,
-                          null_as_string: &str) -> Option<Column> {
+/// converts every field to the given type, None if any field doesn't convert
+fn convert_as<S: AsRef<str>>(raw_values: &[Option<S>], column_type: &ColumnType, null_as_string: &str, formats: &Formats) -> Option<Column> {
+    match column_type {
+        ColumnType::Boolean => convert_into_column(raw_values, booleans(formats), null_as_string),
+        ColumnType::Date => convert_into_column(raw_values, dates(formats, false), null_as_string),
+        ColumnType::Int => convert_into_column(raw_values, ints(formats), null_as_string),
+        ColumnType::Float => convert_into_column(raw_values, floats(formats), null_as_string),
+        ColumnType::String => Some(strings(raw_values, null_as_string)),
+    }
+}
+
+fn convert_or_null<T, S: AsRef<str>>(raw_values: &[Option<S>], mut converter: Box<dyn Converter<T>>,
+                                     null_as_string: &str) -> (Column, usize) {
+    let mut mismatches = 0;
+
+    let target = raw_values.iter().map(|raw_value| {
+        match raw_value.as_ref().map(|s| s.as_ref()) {
+            Some(raw_value) if raw_value != null_as_string => {
+                let converted = converter.convert(raw_value);
+
+                if converted.is_none() {
+                    mismatches += 1;
+                }
+
+                converted
+            },
+            _ => None,
+        }
+    }).collect();
+
+    (converter.make_column(target), mismatches)
+}
+
+fn convert_into_column<T, S: AsRef<str>>(raw_values: &[Option<S>], mut converter: Box<dyn Converter<T>>,
+                                         null_as_string: &str) -> Option<Column> {
     let mut target = vec!();
 
     for raw_value in raw_values {
-        let value = if raw_value == null_as_string || raw_value.len() == 0 {
-            None
-        } else {
-            // if the converter fails on a null value then we reject the column from being this type
-            Some(converter.convert(raw_value.as_str())?)
+        let value = match raw_value.as_ref().map(|s| s.as_ref()) {
+            Some(raw_value) if raw_value != null_as_string && !raw_value.is_empty() => {
+                // if the converter fails on a null value then we reject the column from being this type
+                Some(converter.convert(raw_value)?)
+            },
+            _ => None,
         };
 
         target.push(value);
@@ -51,8 +198,9 @@ fn convert_into_column<T>(raw_values: &Vec<String>, mut converter: Box<dyn Conve
 
 #[cfg(test)]
 mod test {
-    use crate::build_column::build_column;
-    use crate::table::Column;
+    use crate::build_column::{build_column, build_typed_column, ColumnBuilder};
+    use crate::table::{Column, ColumnType};
+    use crate::ingest::Formats;
     #[test]
     fn build_booleans() {
         let raw_booleans = vec!("true", "false", "false").iter().map(|v| v.to_string()).collect();
@@ -106,4 +254,143 @@ mod test {
         }
     }
 
+    #[test]
+    fn quoted_empty_string() {
+        let fields = vec![Some("".to_string()), None, Some("a".to_string())];
+
+        let mut builder = ColumnBuilder::with_formats(None, &Formats::default());
+        builder.push(&fields, "null");
+
+        match builder.finish("null").0 {
+            Column::Strings(s) => {
+                assert_eq!(s, vec![Some("".to_string()), None, Some("a".to_string())]);
+            }
+
+            _ => {
+                assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn typed_column() {
+        let fields = vec![Some("1".to_string()), Some("x".to_string()), None, Some("3".to_string())];
+
+        match build_typed_column(fields.clone(), "null", &ColumnType::Int) {
+            (Column::Ints(i), mismatches) => {
+                assert_eq!(i, vec![Some(1), None, None, Some(3)]);
+                assert_eq!(mismatches, 1);
+            },
+            _ => assert!(false),
+        }
+
+        match build_typed_column(fields, "null", &ColumnType::String) {
+            (Column::Strings(s), 0) => assert_eq!(s[0].as_deref(), Some("1")),
+            _ => assert!(false),
+        }
+    }
+
+    fn fields(values: &[&str]) -> Vec<Option<String>> {
+        values.iter().map(|v| if v.is_empty() { None } else { Some(v.to_string()) }).collect()
+    }
+
+    #[test]
+    fn chunks_promote_their_column() {
+        let mut builder = ColumnBuilder::with_formats(None, &Formats::default());
+        builder.push(&fields(&["", ""]), "null");
+        builder.push(&fields(&["1", "2"]), "null");
+        builder.push(&fields(&["2.5"]), "null");
+
+        match builder.finish("null") {
+            (Column::Floats(f), 0) => assert_eq!(f, vec![None, None, Some(1.0), Some(2.0), Some(2.5)]),
+            _ => assert!(false),
+        }
+
+        let mut builder = ColumnBuilder::with_formats(None, &Formats::default());
+        builder.push(&fields(&["1", "2"]), "null");
+        builder.push(&fields(&["n/a", ""]), "null");
+        builder.push(&fields(&["007"]), "null");
+
+        match builder.finish("null") {
+            (Column::Strings(s), 0) => assert_eq!(s, fields(&["1", "2", "n/a", "", "007"])),
+            _ => assert!(false),
+        }
+
+        // earlier chunks are written as the numbers they were read as, and the builder says so
+        let mut builder = ColumnBuilder::with_formats(None, &Formats::default());
+        builder.push(&fields(&["00042", "1"]), "null");
+        builder.push(&fields(&["7"]), "null");
+        assert!(!builder.promoted());
+        builder.push(&fields(&["x", "null"]), "null");
+        builder.push(&fields(&["9"]), "null");
+        assert!(builder.promoted());
+
+        match builder.finish("null") {
+            (Column::Strings(s), 0) => assert_eq!(s[0].as_deref(), Some("42")),
+            _ => assert!(false),
+        }
+
+        let mut builder = ColumnBuilder::with_formats(Some(ColumnType::Int), &Formats::default());
+        builder.push(&fields(&["1", "x"]), "null");
+        builder.push(&fields(&["y"]), "null");
+
+        match builder.finish("null") {
+            (Column::Ints(i), 2) => assert_eq!(i, vec![Some(1), None, None]),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn formats_change_inference() {
+        // numbers written for people are strings unless asked for
+        let mut builder = ColumnBuilder::with_formats(None, &Formats::default());
+        builder.push(&fields(&["1,234.5", "$3", "12%"]), "null");
+
+        match builder.finish("null").0 {
+            Column::Strings(_) => assert!(true),
+            _ => assert!(false),
+        }
+
+        let formatted = Formats { formatted_numbers: true, ..Formats::default() };
+
+        let mut builder = ColumnBuilder::with_formats(None, &formatted);
+        builder.push(&fields(&["1,234.5", "$3", "12%"]), "null");
+
+        match builder.finish("null").0 {
+            Column::Floats(f) => assert_eq!(f, vec![Some(1234.5), Some(3.0), Some(0.12)]),
+            _ => assert!(false),
+        }
+
+        // a literal in a query is read plainly
+        match build_column(vec!["1,234".to_string()], "null") {
+            Column::Strings(_) => assert!(true),
+            _ => assert!(false),
+        }
+
+        let formats = Formats { yes_no: true, ..Formats::default() };
+
+        let mut builder = ColumnBuilder::with_formats(None, &formats);
+        builder.push(&fields(&["Y", "n", ""]), "null");
+
+        match builder.finish("null") {
+            (Column::Booleans(b), 0) => assert_eq!(b, vec![Some(true), Some(false), None]),
+            _ => assert!(false),
+        }
+
+        let mut builder = ColumnBuilder::with_formats(None, &Formats::default());
+        builder.push(&fields(&["1", "0"]), "null");
+
+        match builder.finish("null") {
+            (Column::Ints(i), 0) => assert_eq!(i, vec![Some(1), Some(0)]),
+            _ => assert!(false),
+        }
+
+        let mut builder = ColumnBuilder::with_formats(Some(ColumnType::Date), &Formats::default());
+        builder.push(&fields(&["1607423400", "2020-12-08 10:30:00"]), "null");
+
+        match builder.finish("null") {
+            (Column::Dates(d), 0) => assert_eq!(d, vec![Some(1607423400), Some(1607423400)]),
+            _ => assert!(false),
+        }
+    }
 }
@@ -0,0 +1,106 @@
+//! arrow ipc files and streams, IE .arrow and .feather (v2). Files are written with a single record batch.
+use std::io::{self, Cursor, Write};
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatchReader;
+use crate::table::Column;
+use super::convert::{from_batch, to_batch};
+use super::invalid;
+
+const MAGIC: &[u8] = b"ARROW1";
+
+/// marks the start of a message in a stream
+const CONTINUATION: u32 = 0xffff_ffff;
+
+pub fn is_arrow(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC) || bytes.starts_with(&CONTINUATION.to_le_bytes())
+}
+
+/// reads an arrow file or stream, every record batch is appended to the columns
+pub fn read_arrow(bytes: Vec<u8>) -> io::Result<Vec<(String, Column)>> {
+    let arrow_error = |e: arrow::error::ArrowError| invalid(&e.to_string());
+
+    let reader: Box<dyn RecordBatchReader> = if bytes.starts_with(MAGIC) {
+        Box::new(FileReader::try_new(Cursor::new(bytes), None).map_err(arrow_error)?)
+    } else {
+        Box::new(StreamReader::try_new(Cursor::new(bytes), None).map_err(arrow_error)?)
+    };
+
+    let schema = reader.schema();
+    let batches = reader.collect::<Result<Vec<_>, _>>().map_err(arrow_error)?;
+
+    from_batch(&arrow::compute::concat_batches(&schema, &batches).map_err(arrow_error)?)
+}
+
+/// writes the columns as an arrow ipc file with one record batch
+pub fn write_arrow(columns: &[(&str, &Column)], out: &mut dyn Write) -> io::Result<()> {
+    let batch = to_batch(columns)?;
+    let arrow_error = |e: arrow::error::ArrowError| invalid(&e.to_string());
+
+    let mut writer = FileWriter::try_new(out, &batch.schema()).map_err(arrow_error)?;
+    writer.write(&batch).map_err(arrow_error)?;
+    writer.finish().map_err(arrow_error)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::columnar::arrow::{read_arrow, write_arrow, is_arrow};
+    use crate::columnar::convert::to_batch;
+    use crate::table::Column;
+    use arrow::ipc::writer::StreamWriter;
+
+    /// written by polars with lz4 compressed buffers and strings as views
+    #[test]
+    fn file_written_by_polars() {
+        let bytes = std::fs::read("tests/data/orders.arrow").unwrap();
+        assert!(is_arrow(&bytes));
+
+        let read = read_arrow(bytes).unwrap();
+
+        match read.iter().map(|(_, column)| column).collect::<Vec<_>>().as_slice() {
+            [Column::Ints(id), Column::Strings(city), Column::Floats(_), Column::Booleans(_),
+             Column::Ints(_), Column::Dates(at), Column::Dates(day)] => {
+                assert_eq!(id, &vec![Some(1), Some(2), None, Some(4), Some(5)]);
+                assert_eq!(city, &vec![Some("Boston".to_string()), Some("Boston".to_string()), None,
+                                       Some("Zürich".to_string()), Some("Boston".to_string())]);
+                assert_eq!(at[1], Some(1_714_645_800));
+                assert_eq!(day[3], Some(-86_400));
+            },
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let columns = vec![
+            ("id", Column::Ints(vec![Some(1), None, Some(-3)])),
+            ("price", Column::Floats(vec![Some(1.5), Some(2.0), None])),
+            ("name", Column::Strings(vec![None, Some("b".to_string()), Some("ç".to_string())])),
+            ("paid", Column::Booleans(vec![Some(true), Some(false), Some(true)])),
+            ("at", Column::Dates(vec![Some(1_600_000_000), None, Some(0)])),
+        ];
+
+        let mut bytes = vec![];
+        write_arrow(&columns.iter().map(|(n, c)| (*n, c)).collect::<Vec<_>>(), &mut bytes).unwrap();
+        assert!(is_arrow(&bytes));
+
+        let read = read_arrow(bytes.clone()).unwrap();
+        assert_eq!(read.len(), 5);
+
+        for ((name, column), (read_name, read_column)) in columns.iter().zip(read.iter()) {
+            assert_eq!(name, read_name);
+            assert_eq!(format!("{:?}", column), format!("{:?}", read_column));
+        }
+
+        assert!(read_arrow(bytes[..100].to_vec()).is_err());
+
+        // streams are read as well as files
+        let batch = to_batch(&columns.iter().map(|(n, c)| (*n, c)).collect::<Vec<_>>()).unwrap();
+        let mut stream = StreamWriter::try_new(vec![], &batch.schema()).unwrap();
+        stream.write(&batch).unwrap();
+        let stream = stream.into_inner().unwrap();
+
+        assert!(is_arrow(&stream));
+        assert_eq!(read_arrow(stream).unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,82 @@
+//! columns to and from arrow arrays
+use std::io;
+use std::sync::Arc;
+use arrow::array::{Array, ArrayRef, AsArray, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray, TimestampSecondArray};
+use arrow::compute::{cast, cast_with_options, CastOptions};
+use arrow::datatypes::{DataType, Date32Type, Date64Type, Float64Type, Int64Type, TimeUnit};
+use crate::table::Column;
+use super::invalid;
+
+/// the columns of a record batch, named as they are in its schema
+pub fn from_batch(batch: &RecordBatch) -> io::Result<Vec<(String, Column)>> {
+    batch.schema().fields().iter().zip(batch.columns()).map(|(field, array)| {
+        Ok((field.name().clone(), to_column(field.name(), array)?))
+    }).collect()
+}
+
+/// reads an array into the column type that holds its values, dates and timestamps become seconds
+/// since 1970, decimals become floats and dictionary encoded columns are read as their values
+fn to_column(name: &str, array: &ArrayRef) -> io::Result<Column> {
+    let cast_to = |to: &DataType| cast(array, to).map_err(|e| invalid(&format!("column {}: {}", name, e)));
+
+    let seconds = |array: ArrayRef, per_second: i64| -> Column {
+        Column::Dates(array.as_primitive::<Int64Type>().iter().map(|v| v.map(|v| v.div_euclid(per_second))).collect())
+    };
+
+    Ok(match array.data_type() {
+        DataType::Null => Column::Strings(vec![None; array.len()]),
+        DataType::Boolean => Column::Booleans(array.as_boolean().iter().collect()),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 |
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 => {
+            Column::Ints(cast_to(&DataType::Int64)?.as_primitive::<Int64Type>().iter().collect())
+        },
+        // unsigned values past the largest int are read as floats rather than wrapped
+        DataType::UInt64 => match cast_with_options(array, &DataType::Int64, &CastOptions { safe: false, ..CastOptions::default() }) {
+            Ok(ints) => Column::Ints(ints.as_primitive::<Int64Type>().iter().collect()),
+            Err(_) => Column::Floats(cast_to(&DataType::Float64)?.as_primitive::<Float64Type>().iter().collect()),
+        },
+        DataType::Float16 | DataType::Float32 | DataType::Float64 | DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => {
+            Column::Floats(cast_to(&DataType::Float64)?.as_primitive::<Float64Type>().iter().collect())
+        },
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => {
+            Column::Strings(cast_to(&DataType::Utf8)?.as_string::<i32>().iter().map(|v| v.map(str::to_string)).collect())
+        },
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView | DataType::FixedSizeBinary(_) => {
+            Column::Strings(cast_to(&DataType::Binary)?.as_binary::<i32>().iter().map(|v| {
+                v.map(|bytes| String::from_utf8_lossy(bytes).to_string())
+            }).collect())
+        },
+        DataType::Date32 => Column::Dates(array.as_primitive::<Date32Type>().iter().map(|d| d.map(|d| d as i64 * 86_400)).collect()),
+        DataType::Date64 => Column::Dates(array.as_primitive::<Date64Type>().iter().map(|d| d.map(|d| d.div_euclid(1_000))).collect()),
+        DataType::Timestamp(unit, _) => {
+            let per_second = match unit {
+                TimeUnit::Second => 1,
+                TimeUnit::Millisecond => 1_000,
+                TimeUnit::Microsecond => 1_000_000,
+                TimeUnit::Nanosecond => 1_000_000_000,
+            };
+
+            seconds(cast_to(&DataType::Int64)?, per_second)
+        },
+        DataType::Dictionary(_, values) => to_column(name, &cast_to(values)?)?,
+        other if other.is_nested() => return Err(invalid(&format!("column {} is nested, only flat columns can be read", name))),
+        other => return Err(invalid(&format!("column {} has type {}, which is not supported", name, other))),
+    })
+}
+
+fn to_array(column: &Column) -> ArrayRef {
+    match column {
+        Column::Ints(v) => Arc::new(Int64Array::from(v.clone())),
+        Column::Floats(v) => Arc::new(Float64Array::from(v.clone())),
+        Column::Booleans(v) => Arc::new(BooleanArray::from(v.clone())),
+        Column::Strings(v) => Arc::new(StringArray::from(v.clone())),
+        Column::Dates(v) => Arc::new(TimestampSecondArray::from(v.clone()).with_timezone("UTC")),
+    }
+}
+
+/// the columns as a single record batch, every column is nullable
+pub fn to_batch(columns: &[(&str, &Column)]) -> io::Result<RecordBatch> {
+    let arrays = columns.iter().map(|(name, column)| (*name, to_array(column), true));
+
+    RecordBatch::try_from_iter_with_nullable(arrays).map_err(|e| invalid(&e.to_string()))
+}
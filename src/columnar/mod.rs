@@ -0,0 +1,14 @@
+//! binary columnar formats, parquet and arrow ipc, read and written with the arrow crates. Only flat
+//! schemas are read, nested columns are rejected with an error.
+mod arrow;
+mod convert;
+mod parquet;
+
+pub use self::arrow::{read_arrow, write_arrow, is_arrow};
+pub use self::parquet::{read_parquet, write_parquet, is_parquet};
+
+use std::io;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
@@ -0,0 +1,104 @@
+//! parquet files, with any of the codecs and encodings the parquet crate reads. Files are written as
+//! a single row group, compressed with snappy.
+use std::io::{self, Write};
+use bytes::Bytes;
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use crate::table::Column;
+use super::convert::{from_batch, to_batch};
+use super::invalid;
+
+const MAGIC: &[u8] = b"PAR1";
+
+pub fn is_parquet(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// reads every row group of a parquet file into named columns
+pub fn read_parquet(bytes: Vec<u8>) -> io::Result<Vec<(String, Column)>> {
+    let parquet_error = |e: parquet::errors::ParquetError| invalid(&e.to_string());
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes)).map_err(parquet_error)?;
+    let schema = reader.schema().clone();
+
+    let batches = reader.build().map_err(parquet_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(&e.to_string()))?;
+
+    let batch = arrow::compute::concat_batches(&schema, &batches).map_err(|e| invalid(&e.to_string()))?;
+
+    from_batch(&batch)
+}
+
+/// writes the columns as a parquet file with a single row group
+pub fn write_parquet(columns: &[(&str, &Column)], out: &mut dyn Write) -> io::Result<()> {
+    let batch = to_batch(columns)?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+
+    let mut bytes = vec![];
+    let mut writer = ArrowWriter::try_new(&mut bytes, batch.schema(), Some(properties)).map_err(|e| invalid(&e.to_string()))?;
+    writer.write(&batch).map_err(|e| invalid(&e.to_string()))?;
+    writer.close().map_err(|e| invalid(&e.to_string()))?;
+
+    out.write_all(&bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::columnar::parquet::{read_parquet, write_parquet, is_parquet};
+    use crate::table::Column;
+
+    /// written by polars in two row groups of dictionary encoded pages, compressed with snappy, and
+    /// again in one row group compressed with zstd
+    #[test]
+    fn files_written_by_polars() {
+        for path in ["tests/data/orders.parquet", "tests/data/orders_zstd.parquet"] {
+            let read = read_parquet(std::fs::read(path).unwrap()).unwrap();
+            let names: Vec<&str> = read.iter().map(|(name, _)| name.as_str()).collect();
+
+            assert_eq!(names, vec!["id", "City", "price", "paid", "qty", "At", "day"]);
+
+            match read.iter().map(|(_, column)| column).collect::<Vec<_>>().as_slice() {
+                [Column::Ints(id), Column::Strings(city), Column::Floats(price), Column::Booleans(paid),
+                 Column::Ints(qty), Column::Dates(at), Column::Dates(day)] => {
+                    assert_eq!(id, &vec![Some(1), Some(2), None, Some(4), Some(5)]);
+                    assert_eq!(city[3].as_deref(), Some("Zürich"));
+                    assert_eq!(city[2], None);
+                    assert_eq!(price, &vec![Some(1.5), None, Some(3.25), Some(4.0), Some(-2.5)]);
+                    assert_eq!(paid[..3], [Some(true), Some(false), None]);
+                    assert_eq!(qty[1], Some(-2));
+                    assert_eq!(at, &vec![Some(1_714_559_400), Some(1_714_645_800), None, Some(0), Some(1_600_000_000)]);
+                    assert_eq!(day, &vec![Some(1_714_521_600), None, Some(0), Some(-86_400), Some(1_714_608_000)]);
+                },
+                _ => assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let columns = vec![
+            ("id", Column::Ints(vec![Some(1), None, Some(-3)])),
+            ("price", Column::Floats(vec![Some(1.5), Some(2.0), None])),
+            ("name", Column::Strings(vec![None, Some("b".to_string()), Some("ç".to_string())])),
+            ("paid", Column::Booleans(vec![Some(true), Some(false), Some(true)])),
+            ("at", Column::Dates(vec![Some(1_600_000_000), None, Some(0)])),
+        ];
+
+        let mut bytes = vec![];
+        write_parquet(&columns.iter().map(|(n, c)| (*n, c)).collect::<Vec<_>>(), &mut bytes).unwrap();
+        assert!(is_parquet(&bytes));
+
+        let read = read_parquet(bytes.clone()).unwrap();
+        assert_eq!(read.len(), 5);
+
+        for ((name, column), (read_name, read_column)) in columns.iter().zip(read.iter()) {
+            assert_eq!(name, read_name);
+            assert_eq!(format!("{:?}", column), format!("{:?}", read_column));
+        }
+
+        assert!(read_parquet(bytes[..bytes.len() - 1].to_vec()).is_err());
+    }
+}
@@ -1,5 +1,24 @@
-use chrono::{DateTime, NaiveDate};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
 use crate::table::{Column, DateTime as Timestamp};
+use crate::ingest::{Formats, Epoch};
+
+/// tried in order once a field isn't rfc 2822 or 3339. A two digit year comes before a four digit one,
+/// so 20-12-08 is in 2020 rather than the year 20.
+const DATE_FORMATS: [&str; 9] = [
+    "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M",
+    "%y-%m-%d", "%Y-%m-%d", "%m/%d/%Y %H:%M:%S%.f", "%m/%d/%Y %H:%M", "%m/%d/%Y",
+];
+
+/// whole numbers past this many seconds, in the year 5138, are taken to be milliseconds
+const LARGEST_SECONDS: i64 = 100_000_000_000;
+
+/// the currency symbols a formatted number can start or end with
+const CURRENCIES: [char; 5] = ['$', '€', '£', '¥', '₹'];
+
+/// the date and time a number of seconds since 1970 is at, None past the years -262143 and 262142
+pub fn date_time(seconds: Timestamp) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp(seconds, 0).map(|d| d.naive_utc())
+}
 
 fn result_to_option<T, E>(result: Result<T, E>) -> Option<T> {
     match result {
@@ -47,53 +66,193 @@ make_default_converter!(ToInt, i64, Ints);
 make_default_converter!(ToFloat, f64, Floats);
 make_default_converter!(ToBool, bool, Booleans);
 
+/// reads y/n, yes/no, t/f and 1/0 as booleans as well as true and false, in any case
+pub struct ToYesNo {}
+
+impl Converter<bool> for ToYesNo {
+    fn convert(&mut self, field: &str) -> Option<bool> {
+        let is_any = |words: &[&str]| words.iter().any(|word| word.eq_ignore_ascii_case(field.trim()));
+
+        if is_any(&["true", "t", "yes", "y", "1"]) {
+            Some(true)
+        } else if is_any(&["false", "f", "no", "n", "0"]) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn make_column(&self, values: Vec<Option<bool>>) -> Column {
+        Column::Booleans(values)
+    }
+}
+
+/// the number in a field written for people, IE -$1,234.50 or 12%, written the way rust parses it, and
+/// whether it was a percentage. None when the field isn't a number, or its thousands aren't grouped by three.
+fn plain_number(field: &str, decimal: char) -> Option<(String, bool)> {
+    let mut rest = field.trim();
+
+    let percent = rest.ends_with('%');
+    rest = rest.trim_end_matches('%').trim_end();
+
+    let mut negative = false;
+    let mut currency = false;
+
+    // the sign and a currency symbol come in either order, IE -$5 or $-5, or the symbol follows, IE 5 €
+    loop {
+        if let (Some(after), false) = (rest.strip_prefix('-'), negative) {
+            negative = true;
+            rest = after.trim_start();
+        } else if let (Some(after), false) = (rest.strip_prefix(&CURRENCIES[..]), currency) {
+            currency = true;
+            rest = after.trim_start();
+        } else {
+            break;
+        }
+    }
+
+    if let (Some(before), false) = (rest.strip_suffix(&CURRENCIES[..]), currency) {
+        currency = true;
+        rest = before.trim_end();
+    }
+
+    if percent && currency {
+        return None;
+    }
+
+    let group = if decimal == ',' { '.' } else { ',' };
+
+    let (whole, fraction) = match rest.split_once(decimal) {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (rest, None),
+    };
+
+    let groups: Vec<&str> = whole.split(group).collect();
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+
+    let grouped = groups.len() == 1 || (groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3));
+
+    if !grouped || !groups.iter().all(|g| digits(g)) || !fraction.is_none_or(digits) {
+        return None;
+    }
+
+    let sign = if negative { "-" } else { "" };
+    let fraction = fraction.map(|f| format!(".{}", f)).unwrap_or_default();
+
+    Some((format!("{}{}{}", sign, groups.concat(), fraction), percent))
+}
+
+/// reads whole numbers written for people, IE 1,234 or $5, as well as plain ones
+pub struct ToFormattedInt {
+    pub decimal: char,
+}
+
+impl Converter<i64> for ToFormattedInt {
+    fn convert(&mut self, field: &str) -> Option<i64> {
+        field.parse::<i64>().ok().or_else(|| {
+            match plain_number(field, self.decimal)? {
+                (number, false) => number.parse().ok(),
+                // a percentage is a fraction
+                (_, true) => None,
+            }
+        })
+    }
+
+    fn make_column(&self, values: Vec<Option<i64>>) -> Column {
+        Column::Ints(values)
+    }
+}
+
+/// reads numbers written for people, IE 1,234.5, $3.50 or 12% as 0.12, as well as plain ones
+pub struct ToFormattedFloat {
+    pub decimal: char,
+}
+
+impl Converter<f64> for ToFormattedFloat {
+    fn convert(&mut self, field: &str) -> Option<f64> {
+        // with a decimal comma a point groups thousands, so 1.234 is not read as it would be in rust
+        let plain = if self.decimal == '.' { field.parse::<f64>().ok() } else { None };
+
+        plain.or_else(|| {
+            let (number, percent) = plain_number(field, self.decimal)?;
+            let number = number.parse::<f64>().ok()?;
+
+            Some(if percent { number / 100.0 } else { number })
+        })
+    }
+
+    fn make_column(&self, values: Vec<Option<f64>>) -> Column {
+        Column::Floats(values)
+    }
+}
+
+/// reads dates, with or without a time of day. The format that last matched is tried first.
 pub struct ToDate {
-    valid_format: String,
-    valid_format_found: bool,
+    formats: Vec<String>,
+    found: Option<usize>,
+    epoch: Option<Epoch>,
 }
 
 impl ToDate {
     pub fn new() -> Self {
-        return ToDate{
-            valid_format: "".to_owned(),
-            valid_format_found: false,
+        Self::with_formats(&Formats::plain(), false)
+    }
+
+    /// the formats given are tried before the built in ones. In a column declared as dates whole
+    /// numbers are also read, as seconds or milliseconds since 1970.
+    pub fn with_formats(formats: &Formats, declared: bool) -> Self {
+        ToDate {
+            formats: formats.dates.iter().cloned().chain(DATE_FORMATS.iter().map(|f| f.to_string())).collect(),
+            found: None,
+            epoch: if declared { Some(formats.epoch) } else { None },
         }
     }
+
+    fn parse(field: &str, format: &str) -> Option<Timestamp> {
+        NaiveDateTime::parse_from_str(field, format).ok()
+            .or_else(|| NaiveDate::parse_from_str(field, format).ok().map(|d| d.and_time(NaiveTime::MIN)))
+            .map(|d| d.and_utc().timestamp())
+    }
+
+    fn since_epoch(&self, field: &str) -> Option<Timestamp> {
+        let number = field.parse::<i64>().ok()?;
+
+        let seconds = match self.epoch? {
+            Epoch::Seconds => number,
+            Epoch::Milliseconds => number.div_euclid(1000),
+            Epoch::Auto if number.abs() >= LARGEST_SECONDS => number.div_euclid(1000),
+            Epoch::Auto => number,
+        };
+
+        date_time(seconds).map(|_| seconds)
+    }
 }
 
 impl Converter<Timestamp> for ToDate {
     fn convert(&mut self, field: &str) -> Option<Timestamp> {
-        if self.valid_format_found {
-            result_to_option(NaiveDate::parse_from_str(field, self.valid_format
-                .as_str()).map(|v| {v.and_hms(0, 0, 0).timestamp()}))
-        } else {
-            match DateTime::parse_from_rfc2822(field) {
-                Err(_) => (),
-                Ok(d) => return Some(d.timestamp()),
-            };
-
-            match DateTime::parse_from_rfc3339(field) {
-                Err(_) => (),
-                Ok(d) => return Some(d.timestamp()),
-            }
+        if let Some(found) = self.found.and_then(|found| Self::parse(field, &self.formats[found])) {
+            return Some(found);
+        }
+
+        match DateTime::parse_from_rfc2822(field) {
+            Err(_) => (),
+            Ok(d) => return Some(d.timestamp()),
+        };
 
-            // we should only hit this part once
-            let formats = vec!("%y-%m-%d", "%Y-%m-%d", "%m/%d/%Y");
+        match DateTime::parse_from_rfc3339(field) {
+            Err(_) => (),
+            Ok(d) => return Some(d.timestamp()),
+        }
 
-            for format in formats {
-                match NaiveDate::parse_from_str(field, format) {
-                    Err(_) => (),
-                    Ok(d) => {
-                        self.valid_format = format.to_owned();
-                        self.valid_format_found = true;
+        for (num, format) in self.formats.iter().enumerate() {
+            if let Some(timestamp) = Self::parse(field, format) {
+                self.found = Some(num);
 
-                        return Some(d.and_hms(0, 0, 0).timestamp());
-                    }
-                }
+                return Some(timestamp);
             }
-
-            None
         }
+
+        self.since_epoch(field)
     }
 
     fn make_column(&self, values: Vec<Option<i64>>) -> Column {
@@ -103,7 +262,8 @@ impl Converter<Timestamp> for ToDate {
 
 #[cfg(test)]
 mod test {
-    use crate::converters::{ToFloat, ToInt, Converter, ToBool, ToDate};
+    use crate::converters::{ToFloat, ToInt, Converter, ToBool, ToDate, ToYesNo, ToFormattedInt, ToFormattedFloat};
+    use crate::ingest::{Formats, Epoch};
 
     #[test]
     fn convert_int() {
@@ -189,4 +349,64 @@ mod test {
             _ => assert!(false),
         }
     }
+    #[test]
+    fn convert_date_times() {
+        let mut converter = ToDate::new();
+
+        assert_eq!(converter.convert("2020-12-08"), Some(1607385600));
+        assert_eq!(converter.convert("2020-12-08 10:30:15.250"), Some(1607423415));
+        assert_eq!(converter.convert("2020-12-08T10:30"), Some(1607423400));
+        assert_eq!(converter.convert("12/08/2020 10:30"), Some(1607423400));
+        assert_eq!(converter.convert("1607423400"), None);
+
+        let mut formats = Formats::default();
+        formats.add_date("%d.%m.%Y %H:%M").unwrap();
+        assert_eq!(formats.add_date("%Q"), None);
+
+        let mut converter = ToDate::with_formats(&formats, true);
+        assert_eq!(converter.convert("08.12.2020 10:30"), Some(1607423400));
+        assert_eq!(converter.convert("1607423400"), Some(1607423400));
+        assert_eq!(converter.convert("1607423400123"), Some(1607423400));
+
+        formats.epoch = Epoch::Milliseconds;
+        assert_eq!(ToDate::with_formats(&formats, true).convert("1607423400000"), Some(1607423400));
+
+        // past the dates there are, rather than failing when shown
+        assert_eq!(ToDate::with_formats(&formats, true).convert("99999999999999999"), None);
+        formats.epoch = Epoch::Seconds;
+        assert_eq!(ToDate::with_formats(&formats, true).convert("99999999999999999"), None);
+        assert_eq!(super::date_time(i64::MAX), None);
+    }
+
+    #[test]
+    fn convert_yes_no() {
+        let mut c = ToYesNo{};
+
+        assert_eq!(c.convert("Y"), Some(true));
+        assert_eq!(c.convert("no"), Some(false));
+        assert_eq!(c.convert("1"), Some(true));
+        assert_eq!(c.convert("FALSE"), Some(false));
+        assert_eq!(c.convert("2"), None);
+    }
+
+    #[test]
+    fn convert_formatted_numbers() {
+        let mut ints = ToFormattedInt{ decimal: '.' };
+        let mut floats = ToFormattedFloat{ decimal: '.' };
+
+        assert_eq!(ints.convert("1,234,567"), Some(1234567));
+        assert_eq!(ints.convert("-$5"), Some(-5));
+        assert_eq!(ints.convert("12%"), None);
+        assert_eq!(ints.convert("1,23"), None);
+
+        assert_eq!(floats.convert("1,234.5"), Some(1234.5));
+        assert_eq!(floats.convert("$3.50"), Some(3.5));
+        assert_eq!(floats.convert("5 €"), Some(5.0));
+        assert_eq!(floats.convert("12%"), Some(0.12));
+        assert_eq!(floats.convert("$12%"), None);
+
+        let mut floats = ToFormattedFloat{ decimal: ',' };
+        assert_eq!(floats.convert("1.234,5"), Some(1234.5));
+        assert_eq!(floats.convert("1.5"), None);
+    }
 }
\ No newline at end of file
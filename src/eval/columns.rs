@@ -158,7 +158,7 @@ fn eval_primary(node: ParserNode, op_context: &OpContext, table: &Table, store:
         ParserNodeType::Query => {
             let table = select::eval(next_node, op_context, store)?;
 
-            let mut columns = table.into_columns();
+            let mut columns = table.into_columns()?;
 
             if columns.len() != 1 {
                 Err(SqlError::new("evaluated selected does not have exactly one column, and cannot be used as an expression", Runtime))
@@ -190,7 +190,7 @@ fn eval_identifier(node: ParserNode, table: &Table) -> SqlResult<NamedColumn> {
             table.column(
                 table_identifier.get_text().as_str(),
                 column_identifier.get_text().as_str(),
-            ).map(|c| {
+            )?.map(|c| {
                 NamedColumn {
                     column: c,
                     name: column_identifier.get_text().clone()
@@ -205,7 +205,7 @@ fn eval_function(node: ParserNode, op_context: &OpContext, table: &Table, store:
     let(_, mut tokens, mut nodes) = node.release();
 
     let table = eval(nodes.pop_front(), op_context, table, store)?;
-    let columns = table.into_columns();
+    let columns = table.into_columns()?;
 
     let op = tokens.pop_front().ok_or(SqlError::new("function without name", Syntax))?;
 
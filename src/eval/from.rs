@@ -1,58 +1,175 @@
 use crate::parser::{ParserNode, ParserNodeType};
-use crate::table::{Store as TableContext, Table, Column, Store, NamedColumn};
+use crate::table::{Store as TableContext, Table, Column, Store, NamedColumn, Partitioned, STDIN_PATH, STDIN_ALIAS};
 use crate::result::{SqlResult, SqlError};
 use crate::ops::OpContext;
 use crate::result::ErrorType::{Syntax, Runtime, Type};
 use std::rc;
 use std::rc::Rc;
 use super::select;
-use std::collections::VecDeque;
+use std::collections::{VecDeque, HashSet};
 use crate::eval::select::AliasMap;
 use crate::eval::columns::eval_expression;
+use crate::eval::load::{options_from_node, unquote};
+use crate::ingest::{LoadOptions, InputFormat};
+use crate::tokenizer::TokenType;
 use std::cmp::max;
 
+/// the parts of a where clause joined by and, IE (year = 2024) and (month = 5) gives both comparisons.
+/// Anything else, an or for one, is a single part.
+fn conjuncts(node: &ParserNode) -> Vec<&ParserNode> {
+    let tokens = node.get_tokens();
+    let children = node.get_children();
 
+    if node.get_type() == &ParserNodeType::Comparison && !tokens.is_empty() && tokens.iter().all(|t| t.get_text().eq_ignore_ascii_case("and")) {
+        return children.iter().flat_map(conjuncts).collect();
+    }
+
+    match node.get_type() {
+        ParserNodeType::Expression | ParserNodeType::Equality | ParserNodeType::Comparison | ParserNodeType::Term
+        | ParserNodeType::Factor | ParserNodeType::Unary | ParserNodeType::Primary if tokens.is_empty() && children.len() == 1 => {
+            let inner = conjuncts(&children[0]);
+
+            if inner.len() > 1 { inner } else { vec![node] }
+        },
+        _ => vec![node],
+    }
+}
+
+/// true if every name in the expression is one of names, function names aside. A nested query could
+/// use anything, so it never is.
+fn only_uses(node: &ParserNode, names: &[&str]) -> bool {
+    let skip = if node.get_type() == &ParserNodeType::Function { 1 } else { 0 };
+
+    node.get_type() != &ParserNodeType::Query
+        && node.get_tokens().iter().skip(skip).filter(|t| t.is_type(TokenType::Identifier)).all(|t| names.contains(&t.get_text().as_str()))
+        && node.get_children().iter().all(|child| only_uses(child, names))
+}
+
+/// wraps part of a where clause back up to an expression, the level eval_expression starts from
+fn as_expression(node: ParserNode) -> ParserNode {
+    let parent = match node.get_type() {
+        ParserNodeType::Term => ParserNodeType::Comparison,
+        ParserNodeType::Comparison => ParserNodeType::Equality,
+        ParserNodeType::Equality => ParserNodeType::Expression,
+        _ => return node,
+    };
+
+    let mut wrapper = ParserNode::new(parent);
+    wrapper.add_child(node);
+
+    as_expression(wrapper)
+}
+
+/// which partitions could hold rows the where clause keeps. Each part of it that only uses the keys is
+/// checked against the keys of every partition, the rest of the clause is left for after the files are read.
+fn prune(partitioned: &Partitioned, alias: &str, where_: Option<&ParserNode>, ops: &OpContext, store: &Store) -> Vec<bool> {
+    let keys = partitioned.keys().with_new_alias(alias.to_string());
+
+    // the keys are read from the folder names, so every column of them is read and counted
+    let meta = keys.meta();
+    let mut keep = vec![true; meta.length];
+    let names: Vec<&str> = meta.columns.iter().map(|(_, name, _)| name.as_str()).chain(Some(alias)).collect();
+
+    for part in where_.and_then(|w| w.get_children().front()).map_or(vec![], conjuncts) {
+        if !only_uses(part, &names) {
+            continue;
+        }
+
+        // a part that can't be checked against the keys alone rules nothing out
+        if let Ok(evaluated) = eval_expression(as_expression(part.clone()), ops, &keys, store) {
+            if let Column::Booleans(matches) = evaluated.column.as_ref() {
+                if matches.len() == keep.len() {
+                    keep.iter_mut().zip(matches).for_each(|(keep, matches)| *keep &= *matches == Some(true));
+                }
+            }
+        }
+    }
+
+    keep
+}
+
+
+/// columns and where_ are used to leave out what a partitioned table doesn't need to read
 fn from_statement_to_table(node: ParserNode,
                            ops: &OpContext,
-                           tables: &TableContext) -> SqlResult<Table> {
+                           tables: &TableContext,
+                           columns: Option<&HashSet<String>>,
+                           where_: Option<&ParserNode>) -> SqlResult<Table> {
 
     let (_, mut tokens, mut children) = node.release();
 
-    if !children.is_empty() {
-        let alias = tokens.pop_front().ok_or(SqlError::new("missing alias on select clause", Runtime))?;
+    match children.pop_front() {
+        Some(child) if child.get_type() == &ParserNodeType::FileSource => {
+            let (_, mut file_tokens, mut options) = child.release();
+
+            let path = file_tokens.pop_front().ok_or(SqlError::new("missing path in from statement", Syntax))?;
 
-        select::eval(children.pop_front().unwrap(), ops, tables).map(|t| {
-            t.with_new_alias(alias.to_string())
-        })
-    } else {
-        let table_name = tokens.pop_front().ok_or(SqlError::new("table name required", Runtime))?;
-        let maybe_alias = tokens.pop_front();
+            let mut defaults = LoadOptions::default();
 
-        tables.get(table_name.get_text()).map(|t| {
-            match maybe_alias {
-                Some(alias) => t.with_new_alias(alias.to_string()),
-                None => t.clone(),
+            // the table function, if any, follows the path
+            if file_tokens.pop_front().filter(|function| function.is("read_json")).is_some() {
+                defaults.format = Some(InputFormat::Json);
             }
-        })
-    }
 
+            let options = options_from_node(options.pop_front(), defaults)?;
+
+            tables.read_file(unquote(path.get_text()), &options).map(|t| {
+                match tokens.pop_front().map(|alias| alias.to_string()).or(options.name) {
+                    Some(alias) => t.with_new_alias(alias),
+                    None => t,
+                }
+            })
+        },
+
+        Some(query) => {
+            let alias = tokens.pop_front().ok_or(SqlError::new("missing alias on select clause", Runtime))?;
+
+            select::eval(query, ops, tables).map(|t| {
+                t.with_new_alias(alias.to_string())
+            })
+        },
+
+        None => {
+            let table_name = tokens.pop_front().ok_or(SqlError::new("table name required", Runtime))?;
+            let maybe_alias = tokens.pop_front();
+
+            if let Some(partitioned) = tables.partitioned(table_name.get_text()) {
+                let alias = maybe_alias.map_or(partitioned.alias(), |alias| alias.to_string());
+                let keep = prune(partitioned, &alias, where_, ops, tables);
+
+                return partitioned.read(&keep, |name| columns.is_none_or(|names| names.contains(name)))
+                    .map(|t| t.with_new_alias(alias));
+            }
+
+            // unless a table was registered under the same name, stdin reads whatever was piped in
+            let table = match tables.get(table_name.get_text()) {
+                Err(_) if table_name.is(STDIN_ALIAS) => tables.read_file(STDIN_PATH, &LoadOptions::default())?,
+                found => found?.clone(),
+            };
+
+            Ok(match maybe_alias {
+                Some(alias) => table.with_new_alias(alias.to_string()),
+                None => table,
+            })
+        }
+    }
 }
 
 fn join(left: Table, right: Table, expression: ParserNode, inner: bool, ops: &OpContext, store: &Store) -> SqlResult<Table> {
 
     // if all false, write left, null --
-    let mut destination_columns: Vec<(&str, Column)> = left.as_columns().into_iter().map(|(name, c) | {
+    let mut destination_columns: Vec<(&str, Column)> = left.as_columns()?.into_iter().map(|(name, c) | {
         (name, c.new_empty())
     }).collect();
 
-    right.as_columns().into_iter().for_each(|(name, c) | {
+    right.as_columns()?.into_iter().for_each(|(name, c) | {
         destination_columns.push((name, c.new_empty()));
     });
 
-    for row in 0..left.len() {
-        let mut temp_table = left.row(row).unwrap();
+    for row in 0..left.len()? {
+        let mut temp_table = left.row(row)?.unwrap();
 
-        for col in right.to_columns().into_iter() {
+        for col in right.to_columns()?.into_iter() {
             temp_table.push(col, Some(right.alias_ref()))
         }
 
@@ -60,20 +177,21 @@ fn join(left: Table, right: Table, expression: ParserNode, inner: bool, ops: &Op
 
         if let Column::Booleans(b) = evaluated.column.as_ref() {
 
-            let selected = temp_table.where_(b);
+            let selected = temp_table.where_(b)?;
+            let selected_rows = selected.len()?;
 
-            for _ in 0..max(1, selected.len()) {
-                for (num, col) in temp_table.to_columns().into_iter().take(left.num_columns()).enumerate() {
+            for _ in 0..max(1, selected_rows) {
+                for (num, col) in temp_table.to_columns()?.into_iter().take(left.num_columns()).enumerate() {
                     destination_columns[num].1.extend(col.column.as_ref());
                 }
             }
 
-            if selected.len() == 0 {
+            if selected_rows == 0 {
                 for num in left.num_columns()..temp_table.num_columns() {
                     destination_columns[num].1.push_null();
                 }
             } else {
-                for (num, col) in selected.to_columns().into_iter().enumerate().skip(left.num_columns()) {
+                for (num, col) in selected.to_columns()?.into_iter().enumerate().skip(left.num_columns()) {
                     destination_columns[num].1.extend(col.column.as_ref());
                 }
             }
@@ -101,7 +219,11 @@ fn join(left: Table, right: Table, expression: ParserNode, inner: bool, ops: &Op
     Ok(t)
 }
 
-pub (super) fn eval(root: ParserNode, ops: &OpContext, store: &Store) -> SqlResult<Table> {
+/// columns is what the query uses, None for all of them. The rest are dropped before anything is read,
+/// so the columns of a file that are never used are never read. The where clause lets a partitioned
+/// table skip the files it rules out.
+pub (super) fn eval(root: ParserNode, ops: &OpContext, store: &Store, columns: Option<&HashSet<String>>,
+                    where_: Option<&ParserNode>) -> SqlResult<Table> {
     let (_, _, mut children)  = root.release();
 
     if children.is_empty() {
@@ -117,7 +239,16 @@ pub (super) fn eval(root: ParserNode, ops: &OpContext, store: &Store) -> SqlResu
     let mut tables: VecDeque<Table> = table_nodes
         .into_iter()
         .map(|node| {
-            from_statement_to_table(node, ops, store)
+            let table = from_statement_to_table(node, ops, store, columns, where_)?;
+
+            let table = match columns {
+                Some(names) => table.project(|name| names.contains(name)),
+                None => table,
+            };
+
+            table.materialize()?;
+
+            Ok(table)
         }).collect::<SqlResult<VecDeque<Table>>>()?;
 
     let first = tables.pop_front().ok_or(SqlError::new("select target not found", Runtime))?;
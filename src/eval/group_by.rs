@@ -23,7 +23,7 @@ pub (super) fn eval(node: ParserNode,
 
     let evaluated_keys = columns::eval(Some(columns_node), op_context, &table, store)?;
 
-    let (assignments, key_tables) = keys_to_assignments(&evaluated_keys);
+    let (assignments, key_tables) = keys_to_assignments(&evaluated_keys)?;
 
     Ok(Grouped {
         groups: key_tables.into_iter().enumerate().map(|(num, mut key_table)| {
@@ -31,21 +31,21 @@ pub (super) fn eval(node: ParserNode,
                 Some(assignment == &num)
             }).collect();
 
-            let selected_rows = table.where_(&selector);
+            let selected_rows = table.where_(&selector)?;
 
-            for column in selected_rows.into_columns() {
+            for column in selected_rows.into_columns()? {
                 // TODO figure out how to add all columns to a group by
                 if let Err(_) = key_table.column_search(&column.name) {
                     key_table.push(column, None)
                 }
             }
 
-            key_table
-        }).collect()
+            Ok(key_table)
+        }).collect::<SqlResult<Vec<Table>>>()?
     })
 }
 
-fn keys_to_assignments(grouped_by_keys: &Table) -> (Vec<usize>, Vec<Table>) {
+fn keys_to_assignments(grouped_by_keys: &Table) -> SqlResult<(Vec<usize>, Vec<Table>)> {
     let mut counter = 0;
 
     let mut hist: HashMap<u64, usize> = HashMap::new();
@@ -53,8 +53,10 @@ fn keys_to_assignments(grouped_by_keys: &Table) -> (Vec<usize>, Vec<Table>) {
     let mut assignments = Vec::new();
     let mut representative_rows = Vec::new();
 
-    for i in 0..grouped_by_keys.len() {
-        let hash = grouped_by_keys.hash_row(i);
+    let rows = grouped_by_keys.len()?;
+
+    for i in 0..rows {
+        let hash = grouped_by_keys.hash_row(i)?;
 
         if !hist.contains_key(&hash) {
             representative_rows.push(i);
@@ -67,12 +69,12 @@ fn keys_to_assignments(grouped_by_keys: &Table) -> (Vec<usize>, Vec<Table>) {
 
     // grab one row per group
     let tables = representative_rows.into_iter().map(|i| {
-        let mut selector = vec![Some(false); grouped_by_keys.len()];
+        let mut selector = vec![Some(false); rows];
         selector[i] = Some(true);
 
         grouped_by_keys.where_(&selector)
-    }).collect();
+    }).collect::<SqlResult<Vec<Table>>>()?;
 
-    (assignments, tables)
+    Ok((assignments, tables))
 
 }
\ No newline at end of file
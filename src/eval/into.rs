@@ -10,7 +10,5 @@ pub (super) fn eval(node: ParserNode, table: &Table) -> SqlResult<()> {
 
     filename = filename.replace("'", "");
 
-    table.write_to_file(&filename);
-
-    Ok(())
+    table.write_to_file(&filename)
 }
\ No newline at end of file
@@ -19,6 +19,6 @@ pub (super) fn eval(node: ParserNode, mut table: Table) -> SqlResult<Table> {
                 SqlError::new("could not parse limit, only integers supported", Type)
             })?;
 
-        Ok(table.limit(limit_by))
+        table.limit(limit_by)
 
 }
\ No newline at end of file
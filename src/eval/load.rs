@@ -0,0 +1,53 @@
+use crate::parser::{ParserNode, ParserNodeType};
+use crate::table::{Table, Store};
+use crate::result::{SqlResult, SqlError};
+use crate::result::ErrorType::Syntax;
+use crate::ingest::LoadOptions;
+
+/// strips the surrounding quotes from a 'literal'
+pub (super) fn unquote(text: &str) -> &str {
+    let text = text.strip_prefix('\'').unwrap_or(text);
+    text.strip_suffix('\'').unwrap_or(text)
+}
+
+/// applies each key = value pair of a WITH (...) clause on top of the defaults
+pub (super) fn options_from_node(node: Option<ParserNode>, mut options: LoadOptions) -> SqlResult<LoadOptions> {
+    if let Some(node) = node {
+        let (_, _, children) = node.release();
+
+        for option in children.into_iter() {
+            let (_, mut tokens, _) = option.release();
+
+            let key = tokens.pop_front().ok_or(SqlError::new("load option requires a name", Syntax))?;
+            let value = tokens.pop_front().ok_or(SqlError::new("load option requires a value", Syntax))?;
+
+            options.set(unquote(key.get_text()).to_lowercase().as_str(), unquote(value.get_text()))?;
+        }
+    }
+
+    Ok(options)
+}
+
+pub (super) fn eval(node: ParserNode, store: &mut Store) -> SqlResult<Table> {
+    let (_, mut tokens, mut children) = node.release();
+
+    let path = tokens.pop_front().ok_or(SqlError::new("load requires a path", Syntax))?;
+    let alias = tokens.pop_front().map(|t| t.to_string());
+
+    let options = options_from_node(children.pop_front()
+                                        .filter(|n| n.get_type() == &ParserNodeType::LoadOptions), LoadOptions::default())?;
+
+    store.load(unquote(path.get_text()), alias.as_deref(), &options)?;
+
+    Ok(Table::new())
+}
+
+pub (super) fn eval_detach(node: ParserNode, store: &mut Store) -> SqlResult<Table> {
+    let (_, mut tokens, _) = node.release();
+
+    let alias = tokens.pop_front().ok_or(SqlError::new("detach requires a table name", Syntax))?;
+
+    store.detach(alias.get_text())?;
+
+    Ok(Table::new())
+}
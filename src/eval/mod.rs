@@ -13,10 +13,13 @@ mod limit;
 mod order_by;
 mod group_by;
 mod into;
+mod load;
 
-pub fn eval(root: ParserNode, op_context: &mut OpContext, table_context: &Store) -> SqlResult<Table> {
+pub fn eval(root: ParserNode, op_context: &mut OpContext, table_context: &mut Store) -> SqlResult<Table> {
     match root.get_type() {
         ParserNodeType::Query => select::eval(root, op_context, table_context),
-        _ => Err(SqlError::new("command not recognized, please use one of [<select...>]", Runtime))
+        ParserNodeType::Load => load::eval(root, table_context),
+        ParserNodeType::Detach => load::eval_detach(root, table_context),
+        _ => Err(SqlError::new("command not recognized, please use one of [<select...>, <load...>, <detach...>]", Runtime))
     }
 }
\ No newline at end of file
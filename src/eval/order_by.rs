@@ -15,7 +15,7 @@ pub (super) fn eval(order_by: ParserNode, table: &Table) -> SqlResult<Table>{
     let (_, _, clauses) = order_by.release();
 
     let orders = parse_into_order(clauses, &table)?;
-    let mut sort_order: Vec<usize> = (0..table.len()).collect();
+    let mut sort_order: Vec<usize> = (0..table.len()?).collect();
 
     sort_order.sort_by(|&i, &j| {
         for order in &orders {
@@ -35,7 +35,7 @@ pub (super) fn eval(order_by: ParserNode, table: &Table) -> SqlResult<Table>{
         Ordering::Equal
     });
 
-    Ok(table.order_by(sort_order))
+    table.order_by(sort_order)
 }
 
 fn parse_into_order(nodes: VecDeque<ParserNode>, table: &Table) -> SqlResult<Vec<Order>> {
@@ -62,7 +62,7 @@ fn parse_into_order(nodes: VecDeque<ParserNode>, table: &Table) -> SqlResult<Vec
 
 #[cfg(test)]
 mod test {
-    use crate::ingest::{CsvFinder, SepFinder};
+    use crate::ingest::LoadOptions;
     use crate::tokenizer::Tokenizer;
     use crate::parser::rdp::RecursiveDescentParser;
     use crate::result::{SqlError, SqlResult};
@@ -72,7 +72,7 @@ mod test {
     #[test]
     fn test_danceability() -> SqlResult<()>{
 
-        let mut table_store = table::Store::from_paths(vec!["tests/data/music.csv".to_string()], &(Box::new(CsvFinder{}) as Box<dyn SepFinder>), "null")
+        let mut table_store = table::Store::from_paths(vec!["tests/data/music.csv".to_string()], &LoadOptions::default())
             .map_err(|_| SqlError::new("", Runtime))?;
 
         let t = Tokenizer::new();
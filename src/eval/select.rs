@@ -1,6 +1,7 @@
 use crate::eval::{split, where_, limit};
 use crate::table::{Table, Store as TableContext};
-use crate::parser::ParserNode;
+use crate::parser::{ParserNode, ParserNodeType};
+use crate::tokenizer::TokenType;
 use crate::ops::OpContext;
 use crate::result::{SqlResult, SqlError};
 use crate::eval::from;
@@ -10,17 +11,52 @@ use super::group_by;
 use super::into;
 use crate::result::ErrorType::Runtime;
 use std::rc::Rc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub (super) type AliasMap = HashMap<String, String>;
 
+/// the names of the columns a query uses, None when a * needs all of them. Queries nested in it
+/// are left out, they pick what they need from their own tables.
+fn referenced_columns(root: &ParserNode) -> Option<HashSet<String>> {
+    let mut names = HashSet::new();
+
+    if collect_names(root, &mut names) {
+        Some(names)
+    } else {
+        None
+    }
+}
+
+/// every identifier is taken as a column name, false when a * was found. count(*) only needs the rows.
+fn collect_names(node: &ParserNode, names: &mut HashSet<String>) -> bool {
+    if node.get_type() == &ParserNodeType::StarOperator {
+        return false;
+    }
+
+    names.extend(node.get_tokens().iter().filter(|t| t.is_type(TokenType::Identifier)).map(|t| t.to_string()));
+
+    let counts_rows = node.get_type() == &ParserNodeType::Function
+        && node.get_tokens().front().is_some_and(|t| t.get_text().eq_ignore_ascii_case("count"));
+
+    node.get_children().iter().all(|child| {
+        match child.get_type() {
+            ParserNodeType::Query => true,
+            ParserNodeType::Columns if counts_rows => child.get_children().iter()
+                .filter(|c| c.get_type() != &ParserNodeType::StarOperator)
+                .all(|c| collect_names(c, names)),
+            _ => collect_names(child, names),
+        }
+    })
+}
+
 pub (super) fn eval(root: ParserNode, op_context: &OpContext,
                     table_context: &TableContext) -> SqlResult<Table> {
 
+    let columns = referenced_columns(&root);
     let parts = split::split(root)?;
 
     let mut table = match parts.from {
-        Some(node) => from::eval(node, op_context, table_context)?,
+        Some(node) => from::eval(node, op_context, table_context, columns.as_ref(), parts.where_.as_ref())?,
         None => Table::new(),
     };
 
@@ -42,7 +78,7 @@ pub (super) fn eval(root: ParserNode, op_context: &OpContext,
 
                 let selected = columns::eval(columns, op_context, &t, table_context)?;
 
-                if selected.len() > 1 {
+                if selected.len()? > 1 {
                     Err(SqlError::new("length of group by result is greater than one, \
                     are you sure you used aggregate functions?", Runtime))
                 } else {
@@ -17,7 +17,7 @@ pub (super) fn eval(node: ParserNode, table: &Table,
 
     match booleans.as_ref() {
         Column::Booleans(b) => {
-            Ok(table.where_(b))
+            table.where_(b)
         }
 
         _ => Err(SqlError::new("where clause must evaluate to a boolean column", Type))
@@ -0,0 +1,111 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+
+/// compressed files are decompressed as they are read
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path).extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "bz2" => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// recognises a compressed file by its first bytes
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            [b'B', b'Z', b'h', ..] => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// reads the first bytes of a file to find its compression, leaving the file at its start
+    pub fn detect(file: &mut File) -> io::Result<Option<Self>> {
+        let mut magic = [0; 4];
+        let read = file.read(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        Ok(Self::from_magic(&magic[..read]))
+    }
+
+    /// reads the decompressed contents of a reader. Files concatenated by gzip or bzip2, as
+    /// parallel compressors write them, are read through to the last member.
+    pub fn decompress<'a, R: Read + 'a>(&self, reader: R) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+            Compression::Zstd => Box::new(zstd::Decoder::new(reader)?),
+            Compression::Bzip2 => Box::new(MultiBzDecoder::new(reader)),
+        })
+    }
+}
+
+/// the path without a compression extension, sales.csv.gz is read as sales.csv
+pub fn strip_compression(path: &str) -> &str {
+    match Compression::from_path(path) {
+        Some(_) => &path[..path.rfind('.').unwrap()],
+        None => path,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::compression::{Compression, strip_compression};
+
+    #[test]
+    fn compression_from_names_and_bytes() {
+        assert_eq!(Compression::from_path("sales.csv.gz"), Some(Compression::Gzip));
+        assert_eq!(Compression::from_path("sales.tsv.zst"), Some(Compression::Zstd));
+        assert_eq!(Compression::from_path("sales.csv"), None);
+
+        assert_eq!(Compression::from_magic(&[0x1f, 0x8b, 0x08]), Some(Compression::Gzip));
+        assert_eq!(Compression::from_magic(b"BZh9"), Some(Compression::Bzip2));
+        assert_eq!(Compression::from_magic(b"a,b"), None);
+
+        assert_eq!(strip_compression("data/sales.csv.gz"), "data/sales.csv");
+        assert_eq!(strip_compression("data/sales.csv"), "data/sales.csv");
+    }
+
+    #[test]
+    fn decompress_each_format() {
+        use std::io::{Read, Write};
+
+        let decompress = |compression: Compression, bytes: &[u8]| {
+            let mut output = vec![];
+            compression.decompress(bytes)?.read_to_end(&mut output).map(|_| output)
+        };
+
+        let gzip = |text: &[u8]| {
+            let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+            encoder.write_all(text).unwrap();
+            encoder.finish().unwrap()
+        };
+
+        // two members, as written by pigz or by appending to a .gz file
+        let mut two = gzip(b"a,b\n");
+        two.extend(gzip(b"1,2\n"));
+        assert_eq!(decompress(Compression::Gzip, &two).unwrap(), b"a,b\n1,2\n".to_vec());
+
+        let zstd = zstd::encode_all(&b"a,b\n"[..], 0).unwrap();
+        assert_eq!(decompress(Compression::Zstd, &zstd).unwrap(), b"a,b\n".to_vec());
+
+        let mut encoder = bzip2::write::BzEncoder::new(vec![], bzip2::Compression::default());
+        encoder.write_all(b"a,b\n").unwrap();
+        assert_eq!(decompress(Compression::Bzip2, &encoder.finish().unwrap()).unwrap(), b"a,b\n".to_vec());
+
+        assert!(decompress(Compression::Gzip, b"not gzip").is_err());
+    }
+}
@@ -0,0 +1,237 @@
+//! decodes text files into utf-8 as they are read, IE exports from legacy systems in latin-1 or windows-1252
+use std::io::{self, BufRead, Read};
+
+const BYTE_ORDER_MARK: &[u8] = b"\xef\xbb\xbf";
+
+/// the characters windows-1252 puts where latin-1 has control characters, 0x80 to 0x9f. The five
+/// bytes it leaves undefined are read as latin-1.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// how many bytes are decoded at a time
+const CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Latin1,
+    Windows1252,
+}
+
+impl Encoding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Some(Encoding::Utf8),
+            "latin-1" | "latin1" | "iso-8859-1" | "iso8859-1" => Some(Encoding::Latin1),
+            "windows-1252" | "cp1252" | "ansi" => Some(Encoding::Windows1252),
+            _ => None,
+        }
+    }
+
+    /// utf-8 unless the start of the file isn't valid utf-8, then windows-1252 which reads
+    /// every byte, and agrees with latin-1 on all of its printable characters
+    pub fn detect(sample: &[u8]) -> Self {
+        match std::str::from_utf8(sample) {
+            Ok(_) => Encoding::Utf8,
+            // a character cut off by the end of the sample
+            Err(e) if e.error_len().is_none() => Encoding::Utf8,
+            Err(_) => Encoding::Windows1252,
+        }
+    }
+
+    fn decode_byte(&self, byte: u8) -> char {
+        match (self, byte) {
+            (Encoding::Windows1252, 0x80..=0x9f) => WINDOWS_1252[(byte - 0x80) as usize],
+            _ => byte as char,
+        }
+    }
+}
+
+/// appends the valid utf-8 in bytes to out, invalid bytes are replaced with U+FFFD. Returns how many
+/// bytes at the end are the start of a character that continues past them.
+fn push_utf8(out: &mut Vec<u8>, mut bytes: &[u8]) -> usize {
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(_) => {
+                out.extend_from_slice(bytes);
+                return 0;
+            },
+            Err(e) => {
+                let valid = e.valid_up_to();
+                out.extend_from_slice(&bytes[..valid]);
+
+                match e.error_len() {
+                    Some(length) => {
+                        out.extend_from_slice(char::REPLACEMENT_CHARACTER.to_string().as_bytes());
+                        bytes = &bytes[valid + length..];
+                    },
+                    None => return bytes.len() - valid,
+                }
+            },
+        }
+    }
+}
+
+/// appends bytes of a single byte encoding as utf-8
+fn push_single_byte(out: &mut Vec<u8>, encoding: Encoding, bytes: &[u8]) {
+    let mut buffer = [0; 4];
+
+    for &byte in bytes {
+        if byte < 0x80 {
+            out.push(byte);
+        } else {
+            out.extend_from_slice(encoding.decode_byte(byte).encode_utf8(&mut buffer).as_bytes());
+        }
+    }
+}
+
+/// reads text in the given encoding as utf-8, without a byte order mark
+pub struct Decoder<R: Read> {
+    reader: R,
+    encoding: Encoding,
+    fallback: bool, // utf-8 that was detected, read as windows-1252 from the first byte that isn't valid
+    input: Vec<u8>,
+    carried: usize, // bytes of a character left at the start of input by the last chunk
+    output: Vec<u8>,
+    at: usize,
+}
+
+impl<R: BufRead> Decoder<R> {
+    pub fn new(mut reader: R, encoding: Encoding) -> io::Result<Self> {
+        if reader.fill_buf()?.starts_with(BYTE_ORDER_MARK) {
+            reader.consume(BYTE_ORDER_MARK.len());
+        }
+
+        Ok(Decoder { reader, encoding, fallback: false, input: vec![0; CHUNK_BYTES], carried: 0, output: vec![], at: 0 })
+    }
+
+    /// reads text in the encoding detected from its start. Utf-8 that turns out not to be further on,
+    /// IE a latin-1 name past the sample, is read as windows-1252 from there rather than replaced.
+    pub fn detecting(mut reader: R) -> io::Result<Self> {
+        let encoding = Encoding::detect(reader.fill_buf()?);
+        let mut decoder = Self::new(reader, encoding)?;
+        decoder.fallback = encoding == Encoding::Utf8;
+
+        Ok(decoder)
+    }
+}
+
+impl<R: Read> Decoder<R> {
+    /// decodes the next chunk into output, leaving it empty at the end of the text
+    fn fill(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.at = 0;
+
+        while self.output.is_empty() {
+            let read = self.reader.read(&mut self.input[self.carried..])?;
+
+            if read == 0 {
+                // a character cut off by the end of the file
+                if self.carried > 0 && self.fallback {
+                    push_single_byte(&mut self.output, Encoding::Windows1252, &self.input[..self.carried]);
+                    self.carried = 0;
+                } else if self.carried > 0 {
+                    self.output.extend_from_slice(char::REPLACEMENT_CHARACTER.to_string().as_bytes());
+                    self.carried = 0;
+                }
+
+                return Ok(());
+            }
+
+            let end = self.carried + read;
+
+            match self.encoding {
+                Encoding::Utf8 => {
+                    let valid = match std::str::from_utf8(&self.input[..end]) {
+                        Err(e) if self.fallback && e.error_len().is_some() => e.valid_up_to(),
+                        _ => end,
+                    };
+
+                    self.carried = push_utf8(&mut self.output, &self.input[..valid]);
+                    self.input.copy_within(end - self.carried..end, 0);
+
+                    if valid < end {
+                        self.encoding = Encoding::Windows1252;
+                        self.fallback = false;
+                        push_single_byte(&mut self.output, self.encoding, &self.input[valid..end]);
+                    }
+                },
+                encoding => push_single_byte(&mut self.output, encoding, &self.input[..end]),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.at == self.output.len() {
+            self.fill()?;
+        }
+
+        let length = buf.len().min(self.output.len() - self.at);
+        buf[..length].copy_from_slice(&self.output[self.at..self.at + length]);
+        self.at += length;
+
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::encoding::{Encoding, Decoder};
+    use std::io::Read;
+
+    fn decode(bytes: &[u8], encoding: Encoding) -> String {
+        let mut text = String::new();
+        Decoder::new(bytes, encoding).unwrap().read_to_string(&mut text).unwrap();
+
+        text
+    }
+
+    #[test]
+    fn decodes_into_utf8() {
+        assert_eq!(decode(b"\xef\xbb\xbfname,city\n", Encoding::Utf8), "name,city\n");
+        assert_eq!(decode(b"caf\xe9 \x80", Encoding::Latin1), "caf\u{e9} \u{80}");
+        assert_eq!(decode(b"caf\xe9 \x80\x93", Encoding::Windows1252), "caf\u{e9} \u{20ac}\u{201c}");
+        assert_eq!(decode(b"ok\xff", Encoding::Utf8), "ok\u{fffd}");
+        assert_eq!(decode(b"cut \xc3", Encoding::Utf8), "cut \u{fffd}");
+
+        // characters split between chunks are put back together
+        let long = "\u{e9}".repeat(super::CHUNK_BYTES);
+        assert_eq!(decode(format!("a{}", long).as_bytes(), Encoding::Utf8), format!("a{}", long));
+    }
+
+    #[test]
+    fn detected_utf8_falls_back_to_windows_1252() {
+        let detect = |bytes: &[u8]| {
+            let mut text = String::new();
+            Decoder::detecting(bytes).unwrap().read_to_string(&mut text).unwrap();
+            text
+        };
+
+        // past the start of the file, where the encoding was detected
+        let ascii = "a".repeat(super::CHUNK_BYTES * 2);
+        assert_eq!(detect(format!("{}\u{e9} caf", ascii).as_bytes()), format!("{}\u{e9} caf", ascii));
+        assert_eq!(detect(&[ascii.as_bytes(), b"caf\xe9 \x93\xc3\xa9"].concat()), format!("{}caf\u{e9} \u{201c}\u{c3}\u{a9}", ascii));
+        assert_eq!(detect(&[ascii.as_bytes(), b"caf\xe9"].concat()), format!("{}caf\u{e9}", ascii));
+        assert_eq!(detect(b"caf\xe9"), "caf\u{e9}");
+
+        // only when it was detected
+        assert_eq!(decode(&[ascii.as_bytes(), b"caf\xe9 x"].concat(), Encoding::Utf8), format!("{}caf\u{fffd} x", ascii));
+    }
+
+    #[test]
+    fn detect_encoding() {
+        assert_eq!(Encoding::detect("caf\u{e9}".as_bytes()), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b"caf\xc3"), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b"caf\xe9,x"), Encoding::Windows1252);
+        assert_eq!(Encoding::from_name("ISO-8859-1"), Some(Encoding::Latin1));
+        assert_eq!(Encoding::from_name("cp1252"), Some(Encoding::Windows1252));
+    }
+}
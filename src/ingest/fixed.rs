@@ -0,0 +1,147 @@
+/// how the columns of aligned text are found, IE the output of docker ps or df
+#[derive(Clone, Debug, PartialEq)]
+pub enum Widths {
+    /// columns start under the words of the header
+    Header,
+    /// the width of every column but the last, which takes the rest of the line
+    Explicit(Vec<usize>),
+}
+
+impl Widths {
+    /// reads a list of widths, IE 12,8,20
+    pub fn parse(list: &str) -> Option<Self> {
+        list.split(&[',', ':'][..])
+            .map(|w| w.trim().parse::<usize>().ok().filter(|&w| w > 0))
+            .collect::<Option<Vec<usize>>>()
+            .map(Widths::Explicit)
+    }
+}
+
+/// the character offset each column starts at
+pub struct FixedWidths {
+    starts: Vec<usize>,
+}
+
+fn is_blank(line: &[char], index: usize) -> bool {
+    line.get(index).map(|c| c.is_whitespace()).unwrap_or(true)
+}
+
+impl FixedWidths {
+    pub fn from_widths(widths: &[usize]) -> Self {
+        let mut starts = vec![0];
+
+        for width in widths.iter().take(widths.len().saturating_sub(1)) {
+            starts.push(starts.last().unwrap() + width);
+        }
+
+        FixedWidths { starts }
+    }
+
+    /// a column starts under each word of the header that has a blank column before it on every line.
+    /// Right aligned values can reach left of their header, so the boundary may move left into the gap
+    /// between words. Words without any such boundary, IE CONTAINER ID, stay one column.
+    pub fn from_lines(lines: &[&str]) -> Self {
+        let lines: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+        let mut starts = vec![0];
+
+        let header = match lines.first() {
+            Some(header) => header,
+            None => return FixedWidths { starts },
+        };
+
+        let blank_everywhere = |index: usize| lines.iter().all(|line| is_blank(line, index));
+        let mut word_end = 0;
+
+        for index in 1..header.len() {
+            // only the first character of each word
+            if is_blank(header, index) || !is_blank(header, index - 1) {
+                continue;
+            }
+
+            // the rightmost start in the gap after the previous word that follows a blank column
+            let boundary = (word_end + 1..=index).rev().find(|&start| blank_everywhere(start - 1));
+
+            if let Some(start) = boundary {
+                // leading blanks, IE before a right aligned first column, don't make a column
+                if start > *starts.last().unwrap() && (0..start).any(|i| !blank_everywhere(i)) {
+                    starts.push(start);
+                }
+            }
+
+            word_end = (index..header.len()).find(|&i| is_blank(header, i)).unwrap_or(header.len());
+        }
+
+        FixedWidths { starts }
+    }
+
+    /// cuts a line at the column boundaries, blank cells are None
+    pub fn split<'a>(&self, line: &'a str) -> Vec<Option<&'a str>> {
+        // where each character starts, and the end of the line
+        let offsets: Vec<usize> = line.char_indices().map(|(at, _)| at).chain(std::iter::once(line.len())).collect();
+        let chars = offsets.len() - 1;
+
+        self.starts.iter().enumerate().map(|(num, &start)| {
+            let end = self.starts.get(num + 1).cloned().unwrap_or(chars).min(chars);
+
+            if start >= end {
+                return None;
+            }
+
+            let cell = line[offsets[start]..offsets[end]].trim();
+
+            if cell.is_empty() {
+                None
+            } else {
+                Some(cell)
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::fixed::{FixedWidths, Widths};
+
+    fn some<'a>(cells: &[&'a str]) -> Vec<Option<&'a str>> {
+        cells.iter().map(|c| if c.is_empty() { None } else { Some(*c) }).collect()
+    }
+
+    #[test]
+    fn columns_from_header() {
+        let lines = vec![
+            "CONTAINER ID   IMAGE     STATUS         PORTS     NAMES",
+            "3f4e8a1b2c9d   nginx     Up 4 minutes   80/tcp    web",
+            "a1b2c3d4e5f6   redis     Exited (0)               cache",
+        ];
+
+        let widths = FixedWidths::from_lines(&lines);
+
+        assert_eq!(widths.split(lines[0]), some(&["CONTAINER ID", "IMAGE", "STATUS", "PORTS", "NAMES"]));
+        assert_eq!(widths.split(lines[2]), some(&["a1b2c3d4e5f6", "redis", "Exited (0)", "", "cache"]));
+    }
+
+    #[test]
+    fn right_aligned_columns() {
+        let lines = vec![
+            "  PID TTY          TIME CMD",
+            "    1 ?        00:00:01 systemd",
+            " 1234 pts/0    00:00:00 bash",
+        ];
+
+        let widths = FixedWidths::from_lines(&lines);
+
+        assert_eq!(widths.split(lines[0]), some(&["PID", "TTY", "TIME", "CMD"]));
+        assert_eq!(widths.split(lines[2]), some(&["1234", "pts/0", "00:00:00", "bash"]));
+    }
+
+    #[test]
+    fn explicit_widths() {
+        assert_eq!(Widths::parse("4,3"), Some(Widths::Explicit(vec![4, 3])));
+        assert_eq!(Widths::parse("4,x"), None);
+
+        let widths = FixedWidths::from_widths(&[4, 3, 10]);
+
+        assert_eq!(widths.split("ab  cdeghi jkl"), some(&["ab", "cde", "ghi jkl"]));
+        assert_eq!(widths.split("ab"), some(&["ab", "", ""]));
+    }
+}
@@ -0,0 +1,61 @@
+//! how fields are read as booleans, numbers and dates, IE whether yes is a boolean or 1.234,5 a number
+use chrono::format::{Item, StrftimeItems};
+
+/// the unit of whole numbers read as dates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Epoch {
+    /// seconds, or milliseconds for numbers too large to be seconds
+    Auto,
+    Seconds,
+    Milliseconds,
+}
+
+impl Epoch {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Some(Epoch::Auto),
+            "s" | "seconds" => Some(Epoch::Seconds),
+            "ms" | "millis" | "milliseconds" => Some(Epoch::Milliseconds),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Formats {
+    /// y/n, t/f, yes/no and 1/0 are booleans along with true and false, so a column of only 1s and 0s is boolean
+    pub yes_no: bool,
+    /// numbers written for people are numbers, IE 1,234.5, $3.50, or 12% which is read as 0.12
+    pub formatted_numbers: bool,
+    /// what comes before the fraction of a formatted number, the other of . and , groups its thousands
+    pub decimal: char,
+    /// tried before the built in date formats, IE %d.%m.%Y %H:%M
+    pub dates: Vec<String>,
+    /// the unit of whole numbers in columns declared as dates
+    pub epoch: Epoch,
+}
+
+impl Formats {
+    /// fields read only the way rust parses them, and dates in the built in formats
+    pub fn plain() -> Self {
+        Formats { yes_no: false, formatted_numbers: false, decimal: '.', dates: vec![], epoch: Epoch::Auto }
+    }
+
+    /// adds a date format in strftime syntax, None if it isn't one
+    pub fn add_date(&mut self, format: &str) -> Option<()> {
+        if StrftimeItems::new(format).any(|item| item == Item::Error) {
+            return None;
+        }
+
+        self.dates.push(format.to_string());
+
+        Some(())
+    }
+}
+
+/// files are read plainly, formatted numbers and yes/no booleans have to be asked for
+impl Default for Formats {
+    fn default() -> Self {
+        Formats::plain()
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read};
+use serde::de::{Deserialize, Deserializer, Visitor, SeqAccess, MapAccess};
+use crate::table::Column;
+
+/// a json value that keeps the keys of objects in the order they were written, so columns
+/// come out in file order
+enum Value {
+    Null,
+    Scalar(Scalar),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+/// the names of the columns and their values
+pub type JsonFields = (Vec<String>, Vec<Vec<Option<Scalar>>>);
+
+/// a value read into a column, json says what type it is so it isn't inferred from its text
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scalar {
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    /// an array, kept as json text
+    Json(String),
+}
+
+impl Scalar {
+    /// the value as text, for columns declared with a type and columns of mixed types
+    pub fn to_field(&self) -> String {
+        match self {
+            Scalar::Boolean(b) => b.to_string(),
+            Scalar::Int(i) => i.to_string(),
+            Scalar::Float(f) => f.to_string(),
+            Scalar::String(s) | Scalar::Json(s) => s.clone(),
+        }
+    }
+}
+
+impl Value {
+    fn to_json(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Scalar(Scalar::String(s)) => serde_json::Value::String(s.clone()).to_string(),
+            Value::Scalar(s) => s.to_field(),
+            Value::Array(values) => format!("[{}]", values.iter().map(|v| v.to_json()).collect::<Vec<String>>().join(",")),
+            Value::Object(entries) => format!("{{{}}}", entries.iter().map(|(key, v)| {
+                format!("{}:{}", serde_json::Value::String(key.clone()), v.to_json())
+            }).collect::<Vec<String>>().join(",")),
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a json value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Scalar(Scalar::Boolean(v)))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Scalar(Scalar::Int(v)))
+    }
+
+    /// a whole number too large for an int is kept as its digits rather than rounded to a float
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Scalar(i64::try_from(v).map_or_else(|_| Scalar::String(v.to_string()), Scalar::Int)))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Scalar(Scalar::Float(v)))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::Scalar(Scalar::String(v.to_string())))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::Scalar(Scalar::String(v)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = vec![];
+
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = vec![];
+
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+
+        Ok(Value::Object(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+
+/// collects rows into columns, a key missing from a row is null
+struct Columns {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    values: Vec<Vec<Option<Scalar>>>,
+    rows: usize,
+}
+
+impl Columns {
+    fn push_row(&mut self, row: Vec<(String, Option<Scalar>)>) {
+        let mut seen = HashSet::new();
+
+        for (name, value) in row {
+            // keys that flatten to the same name, IE "Id" and "id" or a_b and {"a": {"b"}}, are
+            // told apart as id and id_2 so neither value is lost
+            let name = (1..).map(|n| match n {
+                1 => name.clone(),
+                n => format!("{}_{}", name, n),
+            }).find(|name| !seen.contains(name)).unwrap();
+            seen.insert(name.clone());
+
+            let num = match self.index.get(&name) {
+                Some(&num) => num,
+                None => {
+                    self.index.insert(name.clone(), self.names.len());
+                    self.names.push(name);
+                    self.values.push(vec![None; self.rows]);
+
+                    self.values.len() - 1
+                }
+            };
+
+            self.values[num].push(value);
+        }
+
+        self.rows += 1;
+
+        for column in self.values.iter_mut() {
+            column.resize(self.rows, None);
+        }
+    }
+}
+
+fn column_name(key: &str) -> String {
+    key.to_lowercase().replace(".", "_").replace(" ", "_")
+}
+
+/// nested objects become parent_child columns, arrays are kept as json text
+fn flatten(prefix: Option<&str>, value: Value, row: &mut Vec<(String, Option<Scalar>)>) {
+    let name = |key: &str| match prefix {
+        Some(prefix) => format!("{}_{}", prefix, column_name(key)),
+        None => column_name(key),
+    };
+
+    match value {
+        Value::Object(entries) => {
+            for (key, value) in entries {
+                let key = name(&key);
+
+                match value {
+                    Value::Object(_) => flatten(Some(&key), value, row),
+                    value => row.push((key, scalar(value))),
+                }
+            }
+        },
+        value => row.push((prefix.unwrap_or("value").to_string(), scalar(value))),
+    }
+}
+
+fn scalar(value: Value) -> Option<Scalar> {
+    match value {
+        Value::Null => None,
+        Value::Scalar(s) => Some(s),
+        value => Some(Scalar::Json(value.to_json())),
+    }
+}
+
+/// the column of a key's values. Json strings stay strings, IE "02134" or "$1,200", numbers and
+/// booleans keep their type, a mix of ints and floats is floats and any other mix is read as text.
+/// A string equal to null_as_string is null, as it is in other files.
+pub fn json_column(values: Vec<Option<Scalar>>, null_as_string: &str) -> Column {
+    let values: Vec<Option<Scalar>> = values.into_iter().map(|value| {
+        value.filter(|v| !matches!(v, Scalar::String(s) if s == null_as_string))
+    }).collect();
+
+    let all = |is: fn(&Scalar) -> bool| values.iter().flatten().all(is);
+
+    if all(|v| matches!(v, Scalar::Boolean(_))) {
+        Column::Booleans(values.iter().map(|v| match v { Some(Scalar::Boolean(b)) => Some(*b), _ => None }).collect())
+    } else if all(|v| matches!(v, Scalar::Int(_))) {
+        Column::Ints(values.iter().map(|v| match v { Some(Scalar::Int(i)) => Some(*i), _ => None }).collect())
+    } else if all(|v| matches!(v, Scalar::Int(_) | Scalar::Float(_))) {
+        Column::Floats(values.iter().map(|v| match v {
+            Some(Scalar::Int(i)) => Some(*i as f64),
+            Some(Scalar::Float(f)) => Some(*f),
+            _ => None,
+        }).collect())
+    } else {
+        Column::Strings(values.iter().map(|v| v.as_ref().map(|v| v.to_field())).collect())
+    }
+}
+
+/// reads an array of objects, or a stream of objects one per line (ndjson), into columns named
+/// in the order keys are first seen
+pub fn read_json<R: Read>(reader: R) -> io::Result<JsonFields> {
+    let mut columns = Columns {
+        names: vec![], index: HashMap::new(), values: vec![], rows: 0,
+    };
+
+    let mut push = |value: Value| -> io::Result<()> {
+        match value {
+            Value::Object(_) => {
+                let mut row = vec![];
+                flatten(None, value, &mut row);
+                columns.push_row(row);
+
+                Ok(())
+            },
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "expected json objects, one per row")),
+        }
+    };
+
+    for value in serde_json::Deserializer::from_reader(reader).into_iter::<Value>() {
+        match value? {
+            Value::Array(values) => {
+                for value in values {
+                    push(value)?;
+                }
+            },
+            value => push(value)?,
+        }
+    }
+
+    if columns.rows == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "file is empty"));
+    }
+
+    Ok((columns.names, columns.values))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::json::{read_json, json_column, Scalar};
+    use crate::table::Column;
+
+    #[test]
+    fn ndjson_with_missing_keys() {
+        let data = "{\"id\": 1, \"user\": {\"Name\": \"a\", \"tags\": [1, 2]}}\n{\"id\": 2, \"level\": null, \"extra\": true}\n";
+
+        let (names, values) = read_json(data.as_bytes()).unwrap();
+
+        assert_eq!(names, vec!["id", "user_name", "user_tags", "level", "extra"]);
+        assert_eq!(values[1], vec![Some(Scalar::String("a".to_string())), None]);
+        assert_eq!(values[2][0], Some(Scalar::Json("[1,2]".to_string())));
+        assert_eq!(values[4], vec![None, Some(Scalar::Boolean(true))]);
+    }
+
+    #[test]
+    fn array_of_objects() {
+        let (names, values) = read_json("[{\"a\": 1.5}, {\"a\": 2}]".as_bytes()).unwrap();
+
+        assert_eq!(names, vec!["a"]);
+        assert_eq!(values[0], vec![Some(Scalar::Float(1.5)), Some(Scalar::Int(2))]);
+
+        assert!(read_json("[1, 2]".as_bytes()).is_err());
+        assert!(read_json("".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn keys_flattened_to_one_name() {
+        let data = "[{\"Id\": 1, \"id\": \"x\", \"a\": {\"b\": 2}, \"a_b\": 3}, {\"id\": 4}]";
+        let (names, values) = read_json(data.as_bytes()).unwrap();
+
+        assert_eq!(names, vec!["id", "id_2", "a_b", "a_b_2"]);
+        assert_eq!(values[0], vec![Some(Scalar::Int(1)), Some(Scalar::Int(4))]);
+        assert_eq!(values[1], vec![Some(Scalar::String("x".to_string())), None]);
+        assert_eq!(values[3], vec![Some(Scalar::Int(3)), None]);
+    }
+
+    #[test]
+    fn columns_keep_json_types() {
+        let data = "{\"zip\": \"02134\", \"price\": \"$1,200\", \"n\": 1, \"x\": 0.1, \"big\": 18446744073709551615}\n\
+                    {\"zip\": \"10001\", \"price\": \"null\", \"n\": 2, \"x\": 2, \"big\": 1}\n";
+
+        let (_, values) = read_json(data.as_bytes()).unwrap();
+        let strings = |values: &[&str]| values.iter().map(|v| Some(v.to_string()).filter(|v| v != "")).collect::<Vec<_>>();
+
+        match values.into_iter().map(|v| json_column(v, "null")).collect::<Vec<_>>().as_slice() {
+            [Column::Strings(zip), Column::Strings(price), Column::Ints(n), Column::Floats(x), Column::Strings(big)] => {
+                assert_eq!(zip, &strings(&["02134", "10001"]));
+                assert_eq!(price, &strings(&["$1,200", ""]));
+                assert_eq!(n, &vec![Some(1), Some(2)]);
+                assert_eq!(x, &vec![Some(0.1), Some(2.0)]);
+                assert_eq!(big, &strings(&["18446744073709551615", "1"]));
+            },
+            _ => assert!(false),
+        }
+    }
+}
@@ -1,53 +1,118 @@
-use std::fmt::Write;
+use std::sync::Arc;
+use crate::result::{SqlResult, SqlError};
+use crate::result::ErrorType::Runtime;
+use crate::columnar::{is_parquet, is_arrow};
+use sqlite::has_sqlite_magic;
+use spreadsheet::detect_workbook;
 
 mod common;
+mod compression;
+mod encoding;
+mod fixed;
+mod formats;
+mod json;
+mod records;
+mod report;
+mod schema;
+mod sniff;
+mod split;
+mod spreadsheet;
+mod sqlite;
+
+pub use compression::{Compression, strip_compression};
+pub use encoding::{Encoding, Decoder};
+pub use fixed::{Widths, FixedWidths};
+pub use formats::{Formats, Epoch};
+pub use json::{read_json, json_column, JsonFields};
+pub use schema::{ColumnSpec, parse_schema, parse_types, read_schema_file, schema_file_for};
+pub use records::{RecordReader, RecordBlocks, RecordPart, RecordSlices};
+pub use report::{LoadReport, Reject};
+pub use sniff::Dialect;
+pub use split::split_fields;
+pub use spreadsheet::{Sheet, Cell, CellRange, read_workbook, is_workbook};
+pub use sqlite::{Database, is_database};
 
 type Index = usize;
 
-pub trait SepFinder {
+/// column names and the raw values of each column, as read from a file before types are inferred
+pub type Fields = (Vec<String>, Vec<Vec<Option<String>>>);
+
+/// separators are shared by the threads that parse a file, so they must be safe to send between them
+pub trait SepFinder: Send + Sync {
 
     /// consumes characters that are related to a separator, returns the new Index.
     fn consume_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> Index;
 
     /// at sep return true if the front of chars points to a separator
     fn at_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> bool;
+
+    /// the byte a separator of a single ascii character is made of. Records are then split by
+    /// scanning their bytes, finders without one are asked about each character.
+    fn byte(&self) -> Option<u8> {
+        None
+    }
 }
 
+impl<T: SepFinder + ?Sized> SepFinder for Box<T> {
+    fn consume_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> Index {
+        self.as_ref().consume_sep(chars, index, length)
+    }
+
+    fn at_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> bool {
+        self.as_ref().at_sep(chars, index, length)
+    }
+
+    fn byte(&self) -> Option<u8> {
+        self.as_ref().byte()
+    }
+}
+
+fn consume_char_sep(sep: char, chars: &[char], mut index: Index, length: Index) -> Index {
+    let mut found_sep = false;
+
+    while index < length {
+        if chars[index] == ' ' {
+            index += 1;
+        } else if chars[index] == sep && !found_sep {
+            found_sep = true;
+            index += 1;
+        } else {
+            break;
+        }
+    }
+
+    index
+}
+
+fn at_char_sep(sep: char, chars: &[char], mut index: Index, length: Index) -> bool {
+    let mut found_sep = false;
+
+    while index < length {
+        if chars[index] == ' ' {
+            index += 1;
+        } else if chars[index] == sep && !found_sep {
+            found_sep = true;
+        } else {
+            break;
+        }
+    }
+
+    found_sep
+}
 
 macro_rules! sep_finder_implementation {
     ($target: ident, $char: expr) => {
         impl SepFinder for $target {
-            fn consume_sep(&self, chars: &Vec<char>, mut index: Index, length: Index) -> Index {
-                let mut found_sep = false;
-
-                while index < length {
-                    if chars[index] == ' ' {
-                        index += 1;
-                    } else if chars[index] == $char && !found_sep {
-                        found_sep = true;
-                        index += 1;
-                    } else {
-                        break;
-                    }
-                }
-
-                index
+            fn consume_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> Index {
+                consume_char_sep($char, chars, index, length)
             }
 
-            fn at_sep(&self, chars: &Vec<char>, mut index: Index, length: Index) -> bool {
-                let mut found_sep = false;
-
-                while index < length {
-                    if chars[index] == ' ' {
-                        index += 1;
-                    } else if chars[index] == $char && !found_sep {
-                        found_sep = true;
-                    } else {
-                        break;
-                    }
-                }
+            fn at_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> bool {
+                at_char_sep($char, chars, index, length)
+            }
 
-                return found_sep;
+            fn byte(&self) -> Option<u8> {
+                std::convert::TryFrom::try_from($char).ok()
             }
         }
     }
@@ -65,6 +130,26 @@ pub struct TsvFinder {}
 
 sep_finder_implementation!(TsvFinder, '\t');
 
+/// reads files separated by an arbitrary character, IE ';' or '|'.
+pub struct CharFinder {
+    pub sep: char,
+}
+
+impl SepFinder for CharFinder {
+    fn consume_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> Index {
+        consume_char_sep(self.sep, chars, index, length)
+    }
+
+    fn at_sep(&self, chars: &Vec<char>, index: Index, length: Index) -> bool {
+        at_char_sep(self.sep, chars, index, length)
+    }
+
+    // a space is never a separator here, it's skipped as padding around one
+    fn byte(&self) -> Option<u8> {
+        Some(self.sep).filter(|&sep| sep.is_ascii() && sep != ' ').map(|sep| sep as u8)
+    }
+}
+
 
 /// reads files with separations of more than one space.
 pub struct SpacesFinder {
@@ -90,35 +175,448 @@ impl SepFinder for SpacesFinder {
     }
 }
 
-pub fn read_line(line: &String, separator_reader: &Box<dyn SepFinder>) -> Vec<String> {
+/// maps the name a user gives a separator, IE 'tab', ',' or '\t', onto a SepFinder.
+pub fn separator_from_str(name: &str) -> Option<Arc<dyn SepFinder>> {
+    let name = unescape(name);
+
+    match name.as_str() {
+        "," | "comma" | "csv" => Some(Arc::new(CsvFinder{})),
+        "\t" | "tab" | "tsv" => Some(Arc::new(TsvFinder{})),
+        "spaces" => Some(Arc::new(SpacesFinder{})),
+        _ => {
+            let mut chars = name.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(sep), None) => Some(Arc::new(CharFinder{sep})),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// expands the escape sequences a user can type at the prompt, IE '\t'
+fn unescape(value: &str) -> String {
+    value.replace("\\t", "\t").replace("\\n", "\n")
+}
+
+/// how the contents of a file are laid out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputFormat {
+    /// records separated by lines, IE csv, tsv or aligned text
+    Delimited,
+    /// an array of objects or one object per line (ndjson)
+    Json,
+    Parquet,
+    /// arrow ipc files and streams, which includes feather v2
+    Arrow,
+    /// a database of many tables, attached rather than read as one table
+    Sqlite,
+    /// excel workbooks, each sheet is read as a table
+    Xlsx,
+    /// excel 97-2003 workbooks
+    Xls,
+    /// opendocument spreadsheets, read like excel workbooks
+    Ods,
+}
+
+impl InputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "csv" | "tsv" | "txt" | "delimited" | "text" => Some(InputFormat::Delimited),
+            "json" | "ndjson" | "jsonl" => Some(InputFormat::Json),
+            "parquet" | "pq" => Some(InputFormat::Parquet),
+            "arrow" | "feather" | "ipc" | "arrows" => Some(InputFormat::Arrow),
+            "sqlite" | "sqlite3" | "db" => Some(InputFormat::Sqlite),
+            "xlsx" | "xlsm" | "excel" => Some(InputFormat::Xlsx),
+            "xls" => Some(InputFormat::Xls),
+            "ods" => Some(InputFormat::Ods),
+            _ => None,
+        }
+    }
+
+    /// the format given by a file's extension, IE .jsonl or .jsonl.gz
+    pub fn from_path(path: &str) -> Option<Self> {
+        std::path::Path::new(strip_compression(path)).extension()
+            .and_then(|e| e.to_str())
+            .and_then(InputFormat::from_name)
+    }
+
+    /// binary formats are known by their magic bytes, json starts with an object or an array,
+    /// anything else is read as records
+    pub fn detect(sample: &[u8]) -> Self {
+        if is_parquet(sample) {
+            return InputFormat::Parquet;
+        }
+
+        if is_arrow(sample) {
+            return InputFormat::Arrow;
+        }
+
+        if has_sqlite_magic(sample) {
+            return InputFormat::Sqlite;
+        }
+
+        if let Some(format) = detect_workbook(sample) {
+            return format;
+        }
+
+        match sample.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') | Some(b'[') => InputFormat::Json,
+            _ => InputFormat::Delimited,
+        }
+    }
+}
+
+/// options that control how a file is read into a table, set on the command line or with
+/// LOAD 'file' WITH (sep='\t', null='NA', header=false). Anything left as None is detected
+/// from the start of each file.
+#[derive(Clone)]
+pub struct LoadOptions {
+    pub separator: Option<Arc<dyn SepFinder>>,
+    pub quote: Option<char>,
+    pub null: String,
+    pub header: Option<bool>,
+    /// the table name, by default the file name minus its extension
+    pub name: Option<String>,
+    /// set for aligned text, where columns are found by position rather than a separator
+    pub widths: Option<Widths>,
+    /// column names, and optionally types, given up front instead of read from the header
+    pub columns: Option<Vec<ColumnSpec>>,
+    /// types for columns by name, these skip inference
+    pub types: Vec<ColumnSpec>,
+    /// fail when a value doesn't match its declared type, or a record has the wrong number of
+    /// fields, instead of reading it as null or skipping the record
+    pub strict: bool,
+    /// records with too few fields are filled out with nulls rather than skipped
+    pub pad_missing: bool,
+    /// a file the raw text of skipped records is added to
+    pub rejects: Option<String>,
+    /// None takes the format from the file's extension, or failing that its contents
+    pub format: Option<InputFormat>,
+    /// what text files are decoded from, None detects it from the start of each file
+    pub encoding: Option<Encoding>,
+    /// how fields are read as booleans, numbers and dates
+    pub formats: Formats,
+    /// the one sheet of a workbook to read, by default every sheet is loaded
+    pub sheet: Option<String>,
+    /// the cells of a sheet to read, by default the ones in use
+    pub range: Option<CellRange>,
+}
+
+impl LoadOptions {
+    pub fn new(separator: Option<Arc<dyn SepFinder>>, null: &str) -> Self {
+        LoadOptions {
+            separator,
+            quote: None,
+            null: null.to_string(),
+            header: None,
+            name: None,
+            widths: None,
+            columns: None,
+            types: vec![],
+            strict: false,
+            pad_missing: false,
+            rejects: None,
+            format: None,
+            encoding: None,
+            formats: Formats::default(),
+            sheet: None,
+            range: None,
+        }
+    }
+
+    /// whether a file read with either gives the same table. A separator is only the same as itself,
+    /// so a separator given again reads the file again
+    pub fn same_as(&self, other: &LoadOptions) -> bool {
+        let separators = match (&self.separator, &other.separator) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+
+        separators && self.quote == other.quote && self.null == other.null && self.header == other.header
+            && self.name == other.name && self.widths == other.widths && self.columns == other.columns
+            && self.types == other.types && self.strict == other.strict && self.pad_missing == other.pad_missing
+            && self.rejects == other.rejects && self.format == other.format && self.encoding == other.encoding
+            && self.formats == other.formats && self.sheet == other.sheet && self.range == other.range
+    }
+
+    /// sets a single option by name, values are given as the user typed them
+    pub fn set(&mut self, key: &str, value: &str) -> SqlResult<()> {
+        match key {
+            "sep" | "separator" | "delimiter" if value == "fixed" || value == "aligned" => {
+                self.widths = Some(Widths::Header);
+            },
+            "widths" => {
+                self.widths = Some(Widths::parse(value)
+                    .ok_or(SqlError::new("widths must be a list of column widths, IE 12,8,20", Runtime))?);
+            },
+            "sep" | "separator" | "delimiter" => {
+                self.widths = None;
+                self.separator = Some(separator_from_str(value)
+                    .ok_or(SqlError::new(format!("unknown separator {}", value).as_str(), Runtime))?);
+            },
+            "quote" => {
+                let mut chars = value.chars();
+
+                self.quote = match (chars.next(), chars.next()) {
+                    (Some(quote), None) => Some(quote),
+                    _ => return Err(SqlError::new("quote must be a single character", Runtime)),
+                };
+            },
+            "null" => self.null = unescape(value),
+            "header" => {
+                self.header = Some(parse_flag(value)
+                    .ok_or(SqlError::new("header must be either true or false", Runtime))?);
+            },
+            "name" | "alias" => self.name = Some(value.to_string()),
+            "columns" | "schema" => self.columns = Some(parse_schema(value)?),
+            "types" => self.types = parse_types(value)?,
+            "format" => {
+                self.format = Some(InputFormat::from_name(value)
+                    .ok_or(SqlError::new(format!("unknown input format {}", value).as_str(), Runtime))?);
+            },
+            "encoding" | "charset" => {
+                self.encoding = Some(Encoding::from_name(value)
+                    .ok_or(SqlError::new(format!("unknown encoding {}, expected utf-8, latin-1 or windows-1252", value).as_str(), Runtime))?);
+            },
+            "yes_no" => {
+                self.formats.yes_no = parse_flag(value)
+                    .ok_or(SqlError::new("yes_no must be either true or false", Runtime))?;
+            },
+            "numbers" => {
+                self.formats.formatted_numbers = match value.to_lowercase().as_str() {
+                    "formatted" => true,
+                    "plain" => false,
+                    _ => return Err(SqlError::new("numbers must be either formatted or plain", Runtime)),
+                };
+            },
+            "decimal" => {
+                self.formats.decimal = match value {
+                    "." | "," => value.chars().next().unwrap(),
+                    _ => return Err(SqlError::new("decimal must be either . or ,", Runtime)),
+                };
+                // only formatted numbers have a decimal to choose
+                self.formats.formatted_numbers = true;
+            },
+            "date_format" | "datetime_format" => {
+                self.formats.add_date(value)
+                    .ok_or(SqlError::new(format!("{} is not a date format, IE %d.%m.%Y %H:%M", value).as_str(), Runtime))?;
+            },
+            "epoch" => {
+                self.formats.epoch = Epoch::from_name(value)
+                    .ok_or(SqlError::new("epoch must be one of s, ms or auto", Runtime))?;
+            },
+            "strict" => {
+                self.strict = parse_flag(value)
+                    .ok_or(SqlError::new("strict must be either true or false", Runtime))?;
+            },
+            "pad_missing" | "pad" => {
+                self.pad_missing = parse_flag(value)
+                    .ok_or(SqlError::new("pad_missing must be either true or false", Runtime))?;
+            },
+            "rejects" => self.rejects = Some(value.to_string()),
+            "sheet" => self.sheet = Some(value.to_string()),
+            "range" => {
+                self.range = Some(CellRange::parse(value)
+                    .ok_or(SqlError::new("range must be a block of cells, IE A1:D20", Runtime))?);
+            },
+            _ => return Err(SqlError::look_up_error(key, "load options")),
+        }
+
+        Ok(())
+    }
+
+    /// sets every option in a comma separated list, IE sep=tab,null=NA,name=sales. Items without
+    /// an = continue the previous value, so widths=12,8,20 reads as one option. A comma in a value
+    /// is written \, IE sep=\, though sep=comma reads better.
+    pub fn set_all(&mut self, list: &str) -> SqlResult<()> {
+        let mut options: Vec<String> = vec![];
+
+        for item in split_list(list).into_iter().filter(|o| !o.is_empty()) {
+            match options.last_mut() {
+                Some(previous) if !item.contains('=') => {
+                    previous.push(',');
+                    previous.push_str(&item);
+                },
+                _ => options.push(item),
+            }
+        }
+
+        for option in options.iter() {
+            let (key, value) = split_option(option)?;
+            self.set(key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// settles how a file is read, detecting anything the user did not set from a sample of its first lines
+    pub fn dialect(&self, sample: &str) -> Dialect {
+        let lines = sniff::sample_lines(sample);
+
+        let quote = self.quote.unwrap_or_else(|| sniff::detect_quote(&lines));
+
+        // text with columns lined up by spaces and no other separator is read by position
+        let widths = match &self.widths {
+            Some(Widths::Explicit(widths)) => Some(FixedWidths::from_widths(widths)),
+            Some(Widths::Header) => Some(FixedWidths::from_lines(&lines)),
+            None if self.separator.is_none() && sniff::looks_aligned(&lines, quote) => Some(FixedWidths::from_lines(&lines)),
+            None => None,
+        };
+
+        let separator = self.separator.clone().unwrap_or_else(|| sniff::detect_separator(&lines, quote));
+        let header = self.header.unwrap_or_else(|| {
+            widths.is_some() || sniff::detect_header(&lines, separator.as_ref(), quote)
+        });
+
+        Dialect {
+            separator, quote, header, widths
+        }
+    }
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions::new(None, "null")
+    }
+}
+
+fn split_option(option: &str) -> SqlResult<(&str, &str)> {
+    let mut parts = option.splitn(2, '=');
+
+    match (parts.next(), parts.next()) {
+        (Some(key), Some(value)) if !key.is_empty() => Ok((key.trim(), value.trim())),
+        _ => Err(SqlError::new(format!("expected key=value, got {}", option).as_str(), Runtime)),
+    }
+}
+
+/// true if the text starts with an option name followed by =, IE sep=
+fn starts_with_option(text: &str) -> bool {
+    match text.find('=') {
+        Some(end) => end > 0 && text[..end].chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        None => false,
+    }
+}
+
+/// reads a path given on the command line with options of its own after a colon,
+/// IE sales.tsv:sep=tab,null=NA,name=sales. Options not given are taken from the defaults.
+pub fn parse_table_path(spec: &str, defaults: &LoadOptions) -> SqlResult<(String, LoadOptions)> {
+    let mut options = defaults.clone();
+
+    // option values may hold colons too, IE types=zip:string, so the first colon before an option is used
+    match spec.match_indices(':').map(|(index, _)| index).find(|&index| starts_with_option(&spec[index + 1..])) {
+        Some(index) if index > 0 => {
+            options.set_all(&spec[index + 1..])?;
+
+            Ok((spec[..index].to_string(), options))
+        },
+        _ => Ok((spec.to_string(), options)),
+    }
+}
+
+/// reads a --table flag, a table name and path followed by its options, IE sales=sales.tsv,sep=tab
+pub fn parse_table_flag(spec: &str, defaults: &LoadOptions) -> SqlResult<(String, LoadOptions)> {
+    let mut options = defaults.clone();
+    let mut parts = spec.splitn(2, ',');
+
+    let (name, path) = split_option(parts.next().unwrap_or_default())?;
+    options.set("name", name)?;
+    options.set_all(parts.next().unwrap_or_default())?;
+
+    Ok((path.to_string(), options))
+}
+
+/// splits a list of options at each comma that is not escaped as \,
+fn split_list(list: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = list.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(',')) => {
+                chars.next();
+                items.last_mut().unwrap().push(',');
+            },
+            (',', _) => items.push(String::new()),
+            _ => items.last_mut().unwrap().push(c),
+        }
+    }
+
+    items
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+pub fn read_line(line: &str, separator_reader: &dyn SepFinder) -> Vec<String> {
+    read_record(line, separator_reader).into_iter().map(|field| field.unwrap_or_default()).collect()
+}
+
+/// splits a record into fields, an unquoted empty field is None while a quoted one ("") is an empty string
+pub fn read_record(line: &str, separator_reader: &dyn SepFinder) -> Vec<Option<String>> {
+    read_record_with_quote(line, separator_reader, '"')
+}
+
+/// read_record for files quoted with something other than ", IE '
+pub fn read_record_with_quote(line: &str, separator_reader: &dyn SepFinder, quote: char) -> Vec<Option<String>> {
+    if let (Some(separator), true) = (separator_reader.byte(), quote.is_ascii()) {
+        return split_fields(line, separator, quote as u8).into_iter().map(|field| field.map(|f| f.into_owned())).collect();
+    }
+
     let chars: Vec<char> = line.chars().collect();
     let mut fields = Vec::new();
     let mut index = 0;
     let length = chars.len();
 
-    while index < length {
-        if separator_reader.at_sep(&chars, index, length) {
-            index = separator_reader.consume_sep(&chars, index, length);
+    loop {
+        let (field, quoted, new_index) = read_quoted_field(&chars, index, length, separator_reader, quote);
+        index = new_index;
+
+        fields.push(if field.is_empty() && !quoted {
+            None
+        } else {
+            Some(field)
+        });
+
+        if index >= length {
+            break;
         }
 
-        let (field, new_index) = read_field(&chars, index, length, separator_reader);
-        index = new_index;
+        index = separator_reader.consume_sep(&chars, index, length);
 
-        fields.push(field);
-    };
+        // a separator made of nothing but whitespace at the end of a line is padding, not an empty field
+        if index >= length && chars[new_index..].iter().all(|c| c.is_whitespace()) {
+            break;
+        }
+    }
 
     fields
 }
 
-pub fn read_field(chars: &Vec<char>, mut index: Index, length: Index, separator_reader: &Box<dyn SepFinder>) -> (String, Index) {
+pub fn read_field(chars: &Vec<char>, index: Index, length: Index, separator_reader: &dyn SepFinder) -> (String, Index) {
+    let (s, _, index) = read_quoted_field(chars, index, length, separator_reader, '"');
+
+    (s, index)
+}
+
+/// reads a single field, returning whether any part of it was quoted
+fn read_quoted_field(chars: &Vec<char>, mut index: Index, length: Index,
+                     separator_reader: &dyn SepFinder, quote: char) -> (String, bool, Index) {
     let mut s = String::new();
+    let mut quoted = false;
 
     while index < length && !separator_reader.at_sep(chars, index, length) {
         match chars[index] {
             // on opening quotes we don't need to check the separator until the next opening/closing character
-            '"' => {
+            c if c == quote => {
                 let c = chars[index];
                 index += 1;
+                quoted = true;
                 // read until we find the next instance of this character
                 index = read_until(c, chars, index, length, &mut s);
             },
@@ -130,19 +628,25 @@ pub fn read_field(chars: &Vec<char>, mut index: Index, length: Index, separator_
         }
     }
 
-    (s, index)
+    (s, quoted, index)
 }
 
+/// reads until the closing character, a doubled character IE "" is an escaped quote
 fn read_until(character: char,
               characters: &Vec<char>, mut index: usize, length: usize,
               target: &mut String) -> Index {
 
     while index < length {
         if characters[index] == character {
-            index += 1;
-            break;
+            if index + 1 < length && characters[index + 1] == character {
+                target.push(character);
+                index += 2;
+            } else {
+                index += 1;
+                break;
+            }
         } else {
-            target.write_char(characters[index]);
+            target.push(characters[index]);
             index += 1;
         }
     }
@@ -153,7 +657,7 @@ fn read_until(character: char,
 
 #[cfg(test)]
 mod test {
-    use crate::ingest::{CsvFinder, read_line, SepFinder, read_field};
+    use crate::ingest::{CsvFinder, SpacesFinder, read_line, read_record, SepFinder, read_field, separator_from_str, LoadOptions, parse_table_path, parse_table_flag, Widths, InputFormat};
 
     #[test]
     fn test_line_read() {
@@ -163,7 +667,7 @@ mod test {
 
         let sep = CsvFinder{};
 
-        let parts = read_line(&line.to_string(), &(Box::new(CsvFinder{}) as Box<dyn SepFinder>));
+        let parts = read_line(line, &(Box::new(CsvFinder{}) as Box<dyn SepFinder>));
 
         parts.into_iter().zip(vec!["this", "is", "a", "4", "'csv'"].into_iter()).for_each(|(l, r)| {
             assert_eq!(l, r);
@@ -180,11 +684,11 @@ mod test {
 
         let sep = Box::new(CsvFinder{}) as Box<dyn SepFinder>;
 
-        let length_header = read_line(&header.to_string(), &sep).len();
+        let length_header = read_line(header, &sep).len();
 
-        let l1_header = read_line(&l1.to_string(), &sep);
-        let l2_header = read_line(&l2.to_string(), &sep);
-        let l3_header = read_line(&l3.to_string(), &sep);
+        let l1_header = read_line(l1, &sep);
+        let l2_header = read_line(l2, &sep);
+        let l3_header = read_line(l3, &sep);
 
         assert_eq!(length_header, l1_header.len());
         assert_eq!(length_header, l2_header.len());
@@ -205,5 +709,136 @@ mod test {
 
     }
 
+    #[test]
+    fn rfc_4180_fields() {
+        let sep = CsvFinder{};
+
+        let fields = read_record("\"a, \"\"quoted\"\" value\",,\"\",\"two\nlines\"", &sep);
+
+        assert_eq!(fields, vec![
+            Some("a, \"quoted\" value".to_string()),
+            None,
+            Some("".to_string()),
+            Some("two\nlines".to_string()),
+        ]);
+
+        assert_eq!(read_record(",b,", &sep), vec![None, Some("b".to_string()), None]);
+    }
+
+    #[test]
+    fn trailing_spaces_are_not_a_field() {
+        let fields = read_line("abc     def        ", &SpacesFinder{});
+
+        assert_eq!(fields, vec!["abc", "def"]);
+    }
+
+    #[test]
+    fn separator_names() {
+        let line = "a;b;c".to_string();
+
+        let sep = separator_from_str(";").unwrap();
+        assert_eq!(read_line(&line, sep.as_ref()).len(), 3);
+
+        let tab = separator_from_str("\\t").unwrap();
+        assert_eq!(read_line("a\tb", tab.as_ref()).len(), 2);
+
+        assert!(separator_from_str("too long").is_none());
+    }
+
+    #[test]
+    fn load_options() {
+        let mut options = LoadOptions::default();
+
+        assert!(options.set("null", "NA").is_ok());
+        assert_eq!(options.null, "NA");
+
+        assert!(options.set("header", "false").is_ok());
+        assert_eq!(options.header, Some(false));
+
+        assert!(options.set("quote", "'").is_ok());
+        assert!(options.set("quote", "''").is_err());
+
+        assert!(options.set_all("widths=4,3,10,null=-").is_ok());
+        assert_eq!(options.widths, Some(Widths::Explicit(vec![4, 3, 10])));
+        assert_eq!(options.null, "-");
+        assert!(options.set("sep", "fixed").is_ok());
+        assert_eq!(options.widths, Some(Widths::Header));
+        assert!(options.set("widths", "4,0").is_err());
+
+        assert!(options.set("format", "ndjson").is_ok());
+        assert_eq!(options.format, Some(InputFormat::Json));
+        assert!(options.set("format", "xml").is_err());
+        assert_eq!(InputFormat::from_path("logs/app.jsonl"), Some(InputFormat::Json));
+        assert_eq!(InputFormat::from_path("logs/app.jsonl.gz"), Some(InputFormat::Json));
+        assert_eq!(InputFormat::from_path("trips.parquet"), Some(InputFormat::Parquet));
+        assert_eq!(InputFormat::from_path("trips.feather"), Some(InputFormat::Arrow));
+        assert_eq!(InputFormat::detect(b"  [{\"a\": 1}]"), InputFormat::Json);
+        assert_eq!(InputFormat::detect(b"a,b"), InputFormat::Delimited);
+        assert_eq!(InputFormat::detect(b"PAR1\x15\x04"), InputFormat::Parquet);
+        assert_eq!(InputFormat::detect(b"ARROW1\0\0"), InputFormat::Arrow);
+        assert_eq!(InputFormat::detect(b"SQLite format 3\0"), InputFormat::Sqlite);
+        assert_eq!(InputFormat::from_path("finance/budget.xlsx"), Some(InputFormat::Xlsx));
+        assert_eq!(InputFormat::from_path("finance/old.xls"), Some(InputFormat::Xls));
+
+        assert!(options.set_all("sheet=Q1,range=B2:D10").is_ok());
+        assert_eq!(options.sheet.as_deref(), Some("Q1"));
+        assert!(options.range.is_some());
+        assert!(options.set("range", "everything").is_err());
+
+        assert!(options.set_all("columns=id:int,name,header=no").is_ok());
+        assert_eq!(options.columns.as_ref().map(|c| c.len()), Some(2));
+        assert_eq!(options.header, Some(false));
+
+        assert!(options.set_all("name=sales,null=").is_ok());
+        assert_eq!(options.name.as_deref(), Some("sales"));
+        assert_eq!(options.null, "");
+        assert!(options.set_all("sep").is_err());
+
+        assert!(options.set("header", "maybe").is_err());
+        assert!(options.set("unknown", "value").is_err());
+    }
+
+    #[test]
+    fn table_paths_with_options() {
+        let defaults = LoadOptions::default();
+
+        let (path, options) = parse_table_path("data/sales.tsv:sep=tab,null=NA,name=sales", &defaults).unwrap();
+        assert_eq!(path, "data/sales.tsv");
+        assert_eq!(options.null, "NA");
+        assert_eq!(options.name.as_deref(), Some("sales"));
+        assert!(options.separator.is_some());
+
+        let (path, options) = parse_table_path("C:data.csv", &defaults).unwrap();
+        assert_eq!(path, "C:data.csv");
+        assert!(options.name.is_none());
+
+        let (path, options) = parse_table_path("C:\\zips.csv:types=zip:string,strict=yes", &defaults).unwrap();
+        assert_eq!(path, "C:\\zips.csv");
+        assert_eq!(options.types.len(), 1);
+        assert!(options.strict);
+
+        let (path, options) = parse_table_flag("ps=ps.txt,sep=spaces", &defaults).unwrap();
+        assert_eq!(path, "ps.txt");
+        assert_eq!(options.name.as_deref(), Some("ps"));
+
+        assert!(parse_table_flag("ps.txt", &defaults).is_err());
+        assert!(parse_table_path("a.csv:color=red", &defaults).is_err());
+    }
+
+    #[test]
+    fn comma_in_an_option() {
+        let defaults = LoadOptions::default();
+        let comma = |options: &LoadOptions| options.separator.as_ref().and_then(|sep| sep.byte());
+
+        let (path, options) = parse_table_path("sales.txt:sep=\\,,null=NA", &defaults).unwrap();
+        assert_eq!(path, "sales.txt");
+        assert_eq!(comma(&options), Some(b','));
+        assert_eq!(options.null, "NA");
+
+        let (_, options) = parse_table_path("sales.txt:sep=comma,widths=12,8", &defaults).unwrap();
+        assert_eq!(comma(&options), Some(b','));
 
+        let (_, options) = parse_table_flag("s=sales.txt,null=a\\,b", &defaults).unwrap();
+        assert_eq!(options.null, "a,b");
+    }
 }
@@ -0,0 +1,282 @@
+use std::io::{BufRead, Read, Result};
+use std::ops::Range;
+use memchr::memchr;
+
+/// how much of a file is read at once when it is parsed in parallel
+const BLOCK_BYTES: usize = 8 * 1024 * 1024;
+
+/// parts smaller than this aren't worth handing to another thread
+const MIN_PART_BYTES: usize = 64 * 1024;
+
+/// reads a file one record at a time, a record continues onto the next line while a quoted
+/// field is still open, so quoted fields may contain line breaks (RFC 4180)
+pub struct RecordReader<R: BufRead> {
+    reader: R,
+    quote: Option<char>,
+    line: usize,
+}
+
+impl<R: BufRead> RecordReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_quote(reader, '"')
+    }
+
+    pub fn with_quote(reader: R, quote: char) -> Self {
+        RecordReader {
+            reader,
+            quote: Some(quote),
+            line: 0,
+        }
+    }
+
+    /// reads one line per record, for text where quotes carry no meaning
+    pub fn lines(reader: R) -> Self {
+        RecordReader {
+            reader,
+            quote: None,
+            line: 0,
+        }
+    }
+
+    /// the line the last record ended on, starting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for RecordReader<R> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = String::new();
+        let mut quotes = 0;
+
+        loop {
+            let start = record.len();
+
+            match self.reader.read_line(&mut record) {
+                Err(e) => return Some(Err(e)),
+                Ok(0) => break,
+                Ok(_) => {
+                    self.line += 1;
+
+                    // doubled quotes count twice, so an odd count means a field is still open
+                    if let Some(quote) = self.quote {
+                        quotes += record[start..].matches(quote).count();
+                    }
+
+                    if quotes % 2 == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if record.is_empty() {
+            return None;
+        }
+
+        // only the line ending of the record itself is dropped, ones inside quotes are data
+        if record.ends_with('\n') {
+            record.pop();
+
+            if record.ends_with('\r') {
+                record.pop();
+            }
+        }
+
+        Some(Ok(record))
+    }
+}
+
+/// the records of text already read, split as RecordReader splits them but borrowed from the text
+pub struct RecordSlices<'a> {
+    text: &'a str,
+    quote: Option<char>,
+    line: usize,
+}
+
+impl<'a> RecordSlices<'a> {
+    /// quote is None for text where quotes carry no meaning
+    pub fn new(text: &'a str, quote: Option<char>) -> Self {
+        RecordSlices { text, quote, line: 0 }
+    }
+
+    /// the line the last record ended on, starting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl<'a> Iterator for RecordSlices<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.text.is_empty() {
+            return None;
+        }
+
+        let mut end = 0;
+        let mut quotes = 0;
+
+        loop {
+            let line_end = memchr(b'\n', &self.text.as_bytes()[end..]).map_or(self.text.len(), |at| end + at + 1);
+            self.line += 1;
+
+            if let Some(quote) = self.quote {
+                quotes += self.text[end..line_end].matches(quote).count();
+            }
+
+            end = line_end;
+
+            if quotes % 2 == 0 || end == self.text.len() {
+                break;
+            }
+        }
+
+        let (record, rest) = self.text.split_at(end);
+        self.text = rest;
+
+        Some(record.strip_suffix('\n').map_or(record, |r| r.strip_suffix('\r').unwrap_or(r)))
+    }
+}
+
+/// a run of whole records within a block, and the line of the file it starts after
+pub struct RecordPart {
+    pub bytes: Range<usize>,
+    pub lines_before: usize,
+}
+
+/// reads a file a block of whole records at a time, each block is cut at record boundaries into
+/// parts that can be parsed on their own. A line break inside a quoted field is not a boundary.
+pub struct RecordBlocks<R: Read> {
+    reader: R,
+    quote: Option<Vec<u8>>,
+    carry: Vec<u8>,
+    line: usize,
+}
+
+impl<R: Read> RecordBlocks<R> {
+    /// quote is None for text where quotes carry no meaning, lines_before is the line the reader starts after
+    pub fn new(reader: R, quote: Option<char>, lines_before: usize) -> Self {
+        RecordBlocks { reader, quote: quote.map(|q| q.to_string().into_bytes()), carry: vec![], line: lines_before }
+    }
+
+    fn is_quote(&self, block: &[u8], at: usize) -> bool {
+        match &self.quote {
+            Some(quote) => block[at] == quote[0] && block[at..].starts_with(quote),
+            None => false,
+        }
+    }
+
+    /// the end of each record from start on, start has to be the start of a record
+    fn record_ends<'a>(&'a self, block: &'a [u8], start: usize) -> impl Iterator<Item = usize> + 'a {
+        let mut quoted = false;
+
+        (start..block.len()).filter(move |&at| {
+            if self.is_quote(block, at) {
+                quoted = !quoted;
+            }
+
+            block[at] == b'\n' && !quoted
+        }).map(|at| at + 1)
+    }
+
+    /// the next block and how it is cut into about the given number of parts, None at the end of the file
+    pub fn next_block(&mut self, parts: usize) -> Result<Option<(Vec<u8>, Vec<RecordPart>)>> {
+        let mut block = std::mem::take(&mut self.carry);
+        let mut last_end = None;
+
+        // a record longer than a block keeps the block growing until it ends
+        let at_end = loop {
+            let before = block.len();
+            (&mut self.reader).take(BLOCK_BYTES as u64).read_to_end(&mut block)?;
+
+            if block.len() == before {
+                break true;
+            }
+
+            last_end = self.record_ends(&block, last_end.unwrap_or(0)).last().or(last_end);
+
+            if last_end.is_some() && block.len() >= BLOCK_BYTES {
+                break false;
+            }
+        };
+
+        if block.is_empty() {
+            return Ok(None);
+        }
+
+        // at the end of the file the last record needn't end with a line break
+        let end = match at_end {
+            true => block.len(),
+            false => last_end.unwrap_or(block.len()),
+        };
+
+        self.carry = block.split_off(end);
+
+        let target = (block.len() / parts.max(1)).max(MIN_PART_BYTES);
+        let mut cut = vec![];
+        let mut start = 0;
+
+        for end in self.record_ends(&block, 0) {
+            if end - start >= target && end < block.len() {
+                cut.push(RecordPart { bytes: start..end, lines_before: 0 });
+                start = end;
+            }
+        }
+
+        cut.push(RecordPart { bytes: start..block.len(), lines_before: 0 });
+
+        // quoted line breaks count as lines too, as they do for RecordReader
+        for part in cut.iter_mut() {
+            part.lines_before = self.line;
+            self.line += block[part.bytes.clone()].iter().filter(|&&b| b == b'\n').count();
+        }
+
+        Ok(Some((block, cut)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::{RecordReader, RecordBlocks, RecordSlices};
+
+    #[test]
+    fn multi_line_records() {
+        let data = "a,b\r\n1,\"first\r\nsecond\"\r\n2,\"\"\"\"\n".as_bytes();
+
+        let records = RecordReader::new(data).collect::<std::io::Result<Vec<String>>>().unwrap();
+
+        assert_eq!(records, vec!["a,b", "1,\"first\r\nsecond\"", "2,\"\"\"\""]);
+
+        let mut slices = RecordSlices::new(std::str::from_utf8(data).unwrap(), Some('"'));
+        assert_eq!(slices.by_ref().take(2).collect::<Vec<_>>(), vec!["a,b", "1,\"first\r\nsecond\""]);
+        assert_eq!(slices.line(), 3);
+        assert_eq!(slices.collect::<Vec<_>>(), vec!["2,\"\"\"\""]);
+    }
+
+    #[test]
+    fn blocks_are_cut_between_records() {
+        let mut data = String::new();
+
+        for i in 0..20000 {
+            data.push_str(&format!("{},\"a\nquoted, field\"\n", i));
+        }
+
+        let mut blocks = RecordBlocks::new(data.as_bytes(), Some('"'), 1);
+        let (block, parts) = blocks.next_block(4).unwrap().unwrap();
+
+        assert_eq!(block.len(), data.len());
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[1].lines_before, 1 + 2 * RecordReader::new(&block[parts[0].bytes.clone()]).count());
+
+        let records: usize = parts.iter().map(|p| RecordReader::new(&block[p.bytes.clone()]).count()).sum();
+        assert_eq!(records, 20000);
+        assert!(blocks.next_block(4).unwrap().is_none());
+    }
+}
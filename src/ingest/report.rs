@@ -0,0 +1,116 @@
+//! what went wrong while reading the records of a file, reported once the table is loaded
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+
+/// how many of the rejected records are shown
+const EXAMPLES: usize = 5;
+
+/// a record that didn't have as many fields as the file has columns
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reject {
+    pub line: usize,
+    pub record: String,
+    pub fields: usize,
+}
+
+/// the records skipped or padded while loading a table. Rejected records are also appended to
+/// the rejects file when one is given.
+pub struct LoadReport {
+    pub table: String,
+    pub columns: usize,
+    pub rejected: usize,
+    pub padded: usize,
+    /// the first few rejected records
+    pub examples: Vec<Reject>,
+    pub rejects_path: Option<String>,
+    rejects_file: Option<File>,
+}
+
+impl LoadReport {
+    pub fn new(table: &str, columns: usize, rejects_path: Option<String>) -> Self {
+        LoadReport { table: table.to_string(), columns, rejected: 0, padded: 0, examples: vec![], rejects_path, rejects_file: None }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rejected == 0 && self.padded == 0
+    }
+
+    /// counts a rejected record, keeping it as an example if it's one of the first and writing it to the rejects file
+    pub fn reject(&mut self, reject: Reject) -> io::Result<()> {
+        self.rejected += 1;
+
+        if let Some(path) = &self.rejects_path {
+            if self.rejects_file.is_none() {
+                self.rejects_file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+            }
+
+            if let Some(file) = self.rejects_file.as_mut() {
+                writeln!(file, "{}", reject.record)?;
+            }
+        }
+
+        if self.examples.len() < EXAMPLES {
+            self.examples.push(reject);
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut counts = vec![];
+
+        if self.rejected > 0 {
+            counts.push(format!("{} records were skipped for not having {} fields", self.rejected, self.columns));
+        }
+
+        if self.padded > 0 {
+            counts.push(format!("{} records with fewer than {} fields were padded with nulls", self.padded, self.columns));
+        }
+
+        write!(f, "{}: {}", self.table, counts.join(", "))?;
+
+        for example in self.examples.iter() {
+            write!(f, "\n  line {}, {} fields: {}", example.line, example.fields, example.record)?;
+        }
+
+        if self.rejected > self.examples.len() {
+            write!(f, "\n  and {} more", self.rejected - self.examples.len())?;
+        }
+
+        match &self.rejects_path {
+            Some(path) if self.rejected > 0 => write!(f, "\n  skipped records were added to {}", path),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::report::{LoadReport, Reject};
+
+    #[test]
+    fn report_keeps_the_first_rejects() {
+        let path = std::env::temp_dir().join(format!("sqlit_rejects_{}.csv", std::process::id()));
+        let mut report = LoadReport::new("sales", 3, Some(path.to_str().unwrap().to_string()));
+
+        for line in 2..10 {
+            report.reject(Reject { line, record: format!("{},x", line), fields: 2 }).unwrap();
+        }
+
+        report.padded = 1;
+
+        let text = report.to_string();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(written.lines().collect::<Vec<_>>(), (2..10).map(|line| format!("{},x", line)).collect::<Vec<_>>());
+        assert_eq!(report.rejected, 8);
+        assert_eq!(report.examples.len(), 5);
+        assert!(text.starts_with("sales: 8 records were skipped for not having 3 fields, \
+                                  1 records with fewer than 3 fields were padded with nulls\n  line 2, 2 fields: 2,x"));
+        assert!(text.contains("\n  and 3 more\n"));
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+use crate::table::ColumnType;
+use crate::result::{SqlResult, SqlError};
+use crate::result::ErrorType::Runtime;
+use crate::ingest::strip_compression;
+
+/// a column named by the user, with a type when it shouldn't be inferred
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub column_type: Option<ColumnType>,
+}
+
+/// reads a list of column names with optional types, IE id:int,name,price:float
+pub fn parse_schema(schema: &str) -> SqlResult<Vec<ColumnSpec>> {
+    schema.split(',').map(|column| {
+        let mut parts = column.trim().splitn(2, |c: char| c == ':' || c.is_whitespace());
+        let name = parts.next().unwrap_or_default().trim();
+
+        if name.is_empty() {
+            return Err(SqlError::new(format!("missing column name in schema {}", schema).as_str(), Runtime));
+        }
+
+        let column_type = match parts.next().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            Some(name) => Some(ColumnType::from_name(name)
+                .ok_or(SqlError::new(format!("unknown column type {}", name).as_str(), Runtime))?),
+            None => None,
+        };
+
+        Ok(ColumnSpec {
+            name: name.to_lowercase(),
+            column_type,
+        })
+    }).collect()
+}
+
+/// reads a list of columns that all have a type, IE zip:string,id:string
+pub fn parse_types(types: &str) -> SqlResult<Vec<ColumnSpec>> {
+    let columns = parse_schema(types)?;
+
+    match columns.iter().find(|c| c.column_type.is_none()) {
+        Some(column) => Err(SqlError::new(format!("no type given for column {}", column.name).as_str(), Runtime)),
+        None => Ok(columns),
+    }
+}
+
+/// the sidecar that declares the types of a file's columns, data/sales.csv and data/sales.csv.gz
+/// have data/sales.schema
+pub fn schema_file_for(path: &str) -> PathBuf {
+    Path::new(strip_compression(path)).with_extension("schema")
+}
+
+/// reads a schema file, one column and its type per line, IE zip: string. Lines starting with # are comments.
+pub fn read_schema_file(path: &Path) -> SqlResult<Vec<ColumnSpec>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| SqlError::io_error(format!("{}: {}", path.display(), e).as_str()))?;
+
+    let lines = text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect::<Vec<&str>>();
+
+    parse_types(&lines.join(","))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::schema::{parse_schema, parse_types, schema_file_for, ColumnSpec};
+    use crate::table::ColumnType;
+
+    #[test]
+    fn schema_strings() {
+        let columns = parse_schema("id:int, Name,price float").unwrap();
+
+        assert_eq!(columns, vec![
+            ColumnSpec { name: "id".to_string(), column_type: Some(ColumnType::Int) },
+            ColumnSpec { name: "name".to_string(), column_type: None },
+            ColumnSpec { name: "price".to_string(), column_type: Some(ColumnType::Float) },
+        ]);
+
+        assert!(parse_schema("id:blob").is_err());
+        assert!(parse_schema("id,,name").is_err());
+    }
+
+    #[test]
+    fn types_and_sidecars() {
+        assert_eq!(parse_types("zip: string").unwrap()[0].column_type, Some(ColumnType::String));
+        assert!(parse_types("zip").is_err());
+
+        assert_eq!(schema_file_for("data/sales.csv").to_string_lossy(), "data/sales.schema");
+        assert_eq!(schema_file_for("data/sales.csv.gz").to_string_lossy(), "data/sales.schema");
+    }
+}
@@ -0,0 +1,224 @@
+use std::sync::Arc;
+use crate::converters::{Converter, ToBool, ToDate, ToFloat};
+use crate::ingest::{SepFinder, CsvFinder, TsvFinder, CharFinder, SpacesFinder, FixedWidths, read_record_with_quote, split_fields};
+use std::borrow::Cow;
+
+/// how many lines from the start of a file are used to detect its dialect
+const SAMPLE_LINES: usize = 20;
+
+/// separators that are tried, in order of preference when they score the same
+const CANDIDATES: [char; 4] = [',', '\t', ';', '|'];
+
+/// how a file is laid out, either set by the user or detected
+pub struct Dialect {
+    pub separator: Arc<dyn SepFinder>,
+    pub quote: char,
+    pub header: bool,
+    /// when set columns are cut by position and the separator is unused
+    pub widths: Option<FixedWidths>,
+}
+
+impl Dialect {
+    /// splits a record into fields
+    pub fn split(&self, record: &str) -> Vec<Option<String>> {
+        self.split_borrowed(record).into_iter().map(|field| field.map(Cow::into_owned)).collect()
+    }
+
+    /// splits a record into fields that borrow from it, only a field that unquoting changed is copied.
+    /// Separators of more than a single ascii character are read a character at a time and copied.
+    pub fn split_borrowed<'a>(&self, record: &'a str) -> Vec<Option<Cow<'a, str>>> {
+        match (&self.widths, self.separator.byte()) {
+            (Some(widths), _) => widths.split(record).into_iter().map(|field| field.map(Cow::Borrowed)).collect(),
+            (None, Some(separator)) if self.quote.is_ascii() => split_fields(record, separator, self.quote as u8),
+            (None, _) => read_record_with_quote(record, self.separator.as_ref(), self.quote).into_iter()
+                .map(|field| field.map(Cow::Owned)).collect(),
+        }
+    }
+}
+
+/// the complete, non empty lines at the start of the sample, a sample usually ends mid line
+pub(super) fn sample_lines(sample: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = sample.lines().collect();
+
+    if !sample.ends_with('\n') && lines.len() > 1 {
+        lines.pop();
+    }
+
+    lines.into_iter()
+        .filter(|l| !l.trim().is_empty())
+        .take(SAMPLE_LINES)
+        .collect()
+}
+
+/// counts a character outside of quoted sections of a line
+fn count_unquoted(line: &str, target: char, quote: char) -> usize {
+    let mut quoted = false;
+
+    line.chars().filter(|&c| {
+        if c == quote {
+            quoted = !quoted;
+        }
+
+        !quoted && c == target
+    }).count()
+}
+
+/// a quote character is one that wraps whole fields, IE ,"a", or ,'a',
+pub(super) fn detect_quote(lines: &[&str]) -> char {
+    let wrapped = |quote: char| -> usize {
+        lines.iter().map(|line| {
+            let chars: Vec<char> = line.chars().collect();
+
+            (0..chars.len()).filter(|&i| {
+                chars[i] == quote
+                    && (i == 0 || i == chars.len() - 1
+                        || CANDIDATES.contains(&chars[i - 1]) || CANDIDATES.contains(&chars[i + 1]))
+            }).count()
+        }).sum()
+    };
+
+    if wrapped('\'') > wrapped('"') {
+        '\''
+    } else {
+        '"'
+    }
+}
+
+/// true when no separator character is found but every line has a run of spaces in it
+pub(super) fn looks_aligned(lines: &[&str], quote: char) -> bool {
+    detect_delimiter(lines, quote).is_none() && has_space_runs(lines)
+}
+
+fn has_space_runs(lines: &[&str]) -> bool {
+    !lines.is_empty() && lines.iter().all(|l| l.trim().contains("  "))
+}
+
+/// picks the candidate that appears the same, non zero, number of times on the most lines,
+/// falling back to columns aligned with runs of spaces and finally to commas
+pub(super) fn detect_separator(lines: &[&str], quote: char) -> Arc<dyn SepFinder> {
+    match detect_delimiter(lines, quote) {
+        Some(separator) => separator,
+        None if has_space_runs(lines) => Arc::new(SpacesFinder{}),
+        None => Arc::new(CsvFinder{}),
+    }
+}
+
+fn detect_delimiter(lines: &[&str], quote: char) -> Option<Arc<dyn SepFinder>> {
+    let mut best: Option<(char, usize, usize)> = None;
+
+    for candidate in CANDIDATES.iter() {
+        let counts: Vec<usize> = lines.iter().map(|l| count_unquoted(l, *candidate, quote)).collect();
+
+        // the most common count, ties go to the larger count
+        let mode = counts.iter()
+            .filter(|&&c| c > 0)
+            .max_by_key(|&&c| (counts.iter().filter(|&&other| other == c).count(), c))
+            .cloned();
+
+        if let Some(mode) = mode {
+            let agreeing = counts.iter().filter(|&&c| c == mode).count();
+
+            // a separator splits the first line the same as most others, commas inside an aligned column don't
+            if counts[0] != mode || agreeing * 2 <= counts.len() {
+                continue;
+            }
+
+            let better = match best {
+                None => true,
+                Some((_, best_agreeing, best_mode)) => (agreeing, mode) > (best_agreeing, best_mode),
+            };
+
+            if better {
+                best = Some((*candidate, agreeing, mode));
+            }
+        }
+    }
+
+    best.map(|(sep, _, _)| match sep {
+        ',' => Arc::new(CsvFinder{}) as Arc<dyn SepFinder>,
+        '\t' => Arc::new(TsvFinder{}),
+        sep => Arc::new(CharFinder{sep}),
+    })
+}
+
+/// true if the field reads as something other than a string, IE a number, boolean or date
+fn is_typed(field: &str) -> bool {
+    ToBool{}.convert(field).is_some()
+        || ToFloat{}.convert(field).is_some()
+        || ToDate::new().convert(field).is_some()
+}
+
+/// the first line is taken to be data rather than a header when every column whose other values
+/// are typed also has a typed first value. A file of only strings is assumed to have a header.
+pub(super) fn detect_header(lines: &[&str], separator: &dyn SepFinder, quote: char) -> bool {
+    if lines.len() < 2 {
+        return true;
+    }
+
+    let rows: Vec<Vec<Option<String>>> = lines.iter().map(|l| read_record_with_quote(l, separator, quote)).collect();
+    let first = &rows[0];
+
+    let mut typed_columns = 0;
+
+    for (num, value) in first.iter().enumerate() {
+        let column_typed = rows[1..].iter()
+            .filter_map(|row| row.get(num).cloned().flatten())
+            .filter(|field| !field.is_empty())
+            .all(|field| is_typed(&field));
+
+        let has_values = rows[1..].iter().any(|row| row.get(num).cloned().flatten().is_some());
+
+        if column_typed && has_values {
+            typed_columns += 1;
+
+            match value {
+                Some(v) if is_typed(v) => (),
+                // a name above a typed column
+                _ => return true,
+            }
+        }
+    }
+
+    typed_columns == 0
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+    use crate::ingest::LoadOptions;
+
+    fn dialect_of(sample: &str) -> (usize, char, bool) {
+        let dialect = LoadOptions::default().dialect(sample);
+
+        (dialect.split(sample.lines().next().unwrap()).len(), dialect.quote, dialect.header)
+    }
+
+    #[test]
+    fn detect_separators() {
+        assert_eq!(dialect_of("a,b,c\n1,2,3\n4,5,6\n"), (3, '"', true));
+        assert_eq!(dialect_of("a\tb\n1\t2\n"), (2, '"', true));
+        assert_eq!(dialect_of("name;price\n\"a;b\";1,5\nc;2,5\n"), (2, '"', true));
+        assert_eq!(dialect_of("a|b|c|d\n1|2|3|4\n"), (4, '"', true));
+        assert_eq!(dialect_of("NAME     STATUS\nweb      Up 4 minutes\n"), (2, '"', true));
+        assert_eq!(dialect_of("ID   PORTS          NAMES\n1    80/tcp, 443    web\n2                   db\n"), (3, '"', true));
+    }
+
+    #[test]
+    fn detect_quote_and_header() {
+        assert_eq!(dialect_of("'a,b',c\n'd',e\n"), (2, '\'', true));
+        assert_eq!(dialect_of("1,2.5,true\n2,3.5,false\n"), (3, '"', false));
+        assert_eq!(dialect_of("x,count\ny,2\n"), (2, '"', true));
+    }
+
+    #[test]
+    fn split_borrows_plain_fields() {
+        let dialect = LoadOptions::default().dialect("a,b\n1,\"x,y\"\n");
+        let fields = dialect.split_borrowed("1,\"x,y\"");
+
+        assert!(matches!(fields[0], Some(Cow::Borrowed("1"))));
+        assert_eq!(fields[1].as_deref(), Some("x,y"));
+
+        let dialect = LoadOptions::default().dialect("NAME     STATUS\nweb      Up 4 minutes\n");
+        assert!(matches!(dialect.split_borrowed("web      Up 4 minutes")[1], Some(Cow::Borrowed("Up 4 minutes"))));
+    }
+}
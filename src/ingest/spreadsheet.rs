@@ -0,0 +1,262 @@
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+use calamine::{Data, Ods, Reader, Sheets, SheetType, Xls, Xlsx};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use crate::converters::date_time;
+use crate::ingest::InputFormat;
+
+/// zip archives start with a local file header
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// xls workbooks are ole compound files
+const OLE_MAGIC: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+/// an ods file's first entry is its uncompressed mimetype
+const ODS_MIMETYPE: &[u8] = b"application/vnd.oasis.opendocument.spreadsheet";
+
+/// how date cells are handed over, rfc 3339 is read as a date without guessing a format
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// the format a workbook is read as going by its first bytes, None when it is not a zip archive
+/// or an ole file
+pub fn detect_workbook(sample: &[u8]) -> Option<InputFormat> {
+    if sample.starts_with(OLE_MAGIC) {
+        return Some(InputFormat::Xls);
+    }
+
+    if !sample.starts_with(ZIP_MAGIC) {
+        return None;
+    }
+
+    if sample.windows(ODS_MIMETYPE.len()).any(|w| w == ODS_MIMETYPE) {
+        Some(InputFormat::Ods)
+    } else {
+        Some(InputFormat::Xlsx)
+    }
+}
+
+/// true when a path should be read sheet by sheet, going by the format given, then the extension
+/// and finally the file's first bytes
+pub fn is_workbook(path: &str, format: Option<InputFormat>) -> bool {
+    match format.or_else(|| InputFormat::from_path(path)) {
+        Some(format) => matches!(format, InputFormat::Xlsx | InputFormat::Xls | InputFormat::Ods),
+        None => {
+            let mut sample = vec![];
+
+            File::open(path).and_then(|f| f.take(128).read_to_end(&mut sample)).is_ok() && detect_workbook(&sample).is_some()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Boolean(bool),
+    /// unix seconds
+    Date(i64),
+}
+
+impl Cell {
+    pub fn is_date(&self) -> bool {
+        matches!(self, Cell::Date(_))
+    }
+
+    /// the cell as a raw field, whole numbers are written without a fraction so they are read as ints
+    pub fn to_field(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            Cell::Number(n) => n.to_string(),
+            Cell::Boolean(b) => b.to_string(),
+            Cell::Date(seconds) => date_time(*seconds).map_or_else(|| seconds.to_string(), |d| d.format(DATE_FORMAT).to_string()),
+        }
+    }
+}
+
+/// a block of cells, IE A1:D20. A side left out runs to the edge of the cells in use, so B3 starts at
+/// B3 and A:C takes whole columns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellRange {
+    pub rows: (Option<usize>, Option<usize>),
+    pub columns: (Option<usize>, Option<usize>),
+}
+
+/// a cell reference as a zero based row and column, either may be left out, IE C or 12
+fn parse_reference(reference: &str) -> Option<(Option<usize>, Option<usize>)> {
+    let reference = reference.trim().replace('$', "").to_uppercase();
+    let split = reference.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(reference.len());
+    let (letters, digits) = reference.split_at(split);
+
+    // the last column of a sheet is XFD
+    let column = match letters {
+        "" => None,
+        _ if letters.len() > 3 => return None,
+        _ => Some(letters.bytes().fold(0, |acc, b| acc * 26 + (b - b'A') as usize + 1) - 1),
+    };
+
+    let row = match digits {
+        "" => None,
+        _ => Some(digits.parse::<usize>().ok().filter(|&r| r > 0)? - 1),
+    };
+
+    Some((row, column))
+}
+
+impl CellRange {
+    pub fn parse(range: &str) -> Option<Self> {
+        let mut parts = range.splitn(2, ':');
+        let (first_row, first_column) = parse_reference(parts.next()?)?;
+
+        let (last_row, last_column) = match parts.next() {
+            Some(last) => parse_reference(last)?,
+            None => (None, None),
+        };
+
+        if first_row.is_none() && first_column.is_none() && last_row.is_none() && last_column.is_none() {
+            return None;
+        }
+
+        Some(CellRange { rows: (first_row, last_row), columns: (first_column, last_column) })
+    }
+}
+
+/// the cells of a sheet that hold something, by zero based row and column
+#[derive(Debug)]
+pub struct Sheet {
+    pub name: String,
+    cells: Vec<(usize, usize, Cell)>,
+}
+
+impl Sheet {
+    /// the cells within a range, or within the cells in use, by row. Rows without a value are left out.
+    pub fn rows(&self, range: Option<&CellRange>) -> Vec<Vec<Option<Cell>>> {
+        if self.cells.is_empty() {
+            return vec![];
+        }
+
+        let bound = |get: fn(&(usize, usize, Cell)) -> usize, min: bool| {
+            let values = self.cells.iter().map(get);
+            if min { values.min().unwrap() } else { values.max().unwrap() }
+        };
+
+        let first_row = range.and_then(|r| r.rows.0).unwrap_or_else(|| bound(|c| c.0, true));
+        let last_row = range.and_then(|r| r.rows.1).unwrap_or_else(|| bound(|c| c.0, false));
+        let first_column = range.and_then(|r| r.columns.0).unwrap_or_else(|| bound(|c| c.1, true));
+        let last_column = range.and_then(|r| r.columns.1).unwrap_or_else(|| bound(|c| c.1, false));
+
+        if first_row > last_row || first_column > last_column {
+            return vec![];
+        }
+
+        let mut rows = vec![vec![None; last_column - first_column + 1]; last_row - first_row + 1];
+
+        for (row, column, cell) in self.cells.iter() {
+            if (first_row..=last_row).contains(row) && (first_column..=last_column).contains(column) {
+                rows[row - first_row][column - first_column] = Some(cell.clone());
+            }
+        }
+
+        rows.into_iter().filter(|row| row.iter().any(|c| c.is_some())).collect()
+    }
+}
+
+/// a cell as calamine reads it, None for empty and error cells. Durations are kept as a number of days.
+fn to_cell(data: &Data) -> Option<Cell> {
+    match data {
+        Data::Empty | Data::Error(_) => None,
+        Data::Int(i) => Some(Cell::Number(*i as f64)),
+        Data::Float(f) => Some(Cell::Number(*f)),
+        Data::Bool(b) => Some(Cell::Boolean(*b)),
+        Data::String(text) => Some(Cell::Text(text.clone())),
+        Data::DateTime(date) if date.is_datetime() => date.as_datetime().map(|d| Cell::Date(d.and_utc().timestamp())),
+        Data::DateTime(duration) => Some(Cell::Number(duration.as_f64())),
+        Data::DateTimeIso(date) => Some(parse_iso_date(date).map(Cell::Date).unwrap_or_else(|| Cell::Text(date.clone()))),
+        Data::DurationIso(duration) => Some(Cell::Text(duration.clone())),
+    }
+}
+
+/// every worksheet of a workbook, in the order they are shown. Chart and macro sheets are left out.
+pub fn read_workbook(bytes: &[u8], format: InputFormat) -> io::Result<Vec<Sheet>> {
+    let bytes = Cursor::new(bytes);
+
+    let mut workbook = match format {
+        InputFormat::Ods => Ods::new(bytes).map(Sheets::Ods).map_err(calamine::Error::Ods),
+        InputFormat::Xls => Xls::new(bytes).map(Sheets::Xls).map_err(calamine::Error::Xls),
+        _ => Xlsx::new(bytes).map(Sheets::Xlsx).map_err(calamine::Error::Xlsx),
+    }.map_err(invalid)?;
+
+    let names: Vec<String> = workbook.sheets_metadata().iter()
+        .filter(|sheet| sheet.typ == SheetType::WorkSheet)
+        .map(|sheet| sheet.name.clone())
+        .collect();
+
+    names.into_iter().map(|name| {
+        let range = workbook.worksheet_range(&name).map_err(invalid)?;
+        let (first_row, first_column) = range.start().unwrap_or((0, 0));
+
+        let cells = range.used_cells()
+            .filter_map(|(row, column, data)| {
+                to_cell(data).map(|cell| (first_row as usize + row, first_column as usize + column, cell))
+            })
+            .collect();
+
+        Ok(Sheet { name, cells })
+    }).collect()
+}
+
+fn invalid(e: calamine::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// dates and times in iso 8601, IE 2024-03-01 or 2024-03-01T10:30:00, as unix seconds
+fn parse_iso_date(value: &str) -> Option<i64> {
+    let value = value.trim_end_matches('Z');
+
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|d| d.and_time(NaiveTime::MIN)))
+        .ok()
+        .map(|d| d.and_utc().timestamp())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ingest::spreadsheet::{read_workbook, detect_workbook, parse_reference, CellRange, Cell};
+    use crate::ingest::InputFormat;
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_reference("B3"), Some((Some(2), Some(1))));
+        assert_eq!(parse_reference("$AA$10"), Some((Some(9), Some(26))));
+        assert_eq!(CellRange::parse("A1:D20"), Some(CellRange { rows: (Some(0), Some(19)), columns: (Some(0), Some(3)) }));
+        assert_eq!(CellRange::parse("b:c"), Some(CellRange { rows: (None, None), columns: (Some(1), Some(2)) }));
+        assert_eq!(CellRange::parse("A0"), None);
+        assert_eq!(CellRange::parse(":"), None);
+    }
+
+    #[test]
+    fn workbooks() {
+        for (path, format) in [("tests/data/budget.xlsx", InputFormat::Xlsx), ("tests/data/budget.ods", InputFormat::Ods)] {
+            let bytes = std::fs::read(path).unwrap();
+            assert_eq!(detect_workbook(&bytes), Some(format));
+
+            let sheets = read_workbook(&bytes, format).unwrap();
+            let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+            assert_eq!(names, vec!["Q1", "Notes"]);
+
+            let rows = sheets[0].rows(None);
+            assert_eq!(rows.len(), 4);
+            assert_eq!(rows[0][0], Some(Cell::Text("Item".to_string())));
+            assert_eq!(rows[1][1], Some(Cell::Number(1200.0)));
+            assert_eq!(rows[1][2], Some(Cell::Date(1704067200)));
+            assert_eq!(rows[3][3], Some(Cell::Boolean(false)));
+
+            let range = CellRange::parse("B2:C3").unwrap();
+            assert_eq!(sheets[0].rows(Some(&range)).len(), 2);
+            assert_eq!(sheets[0].rows(Some(&range))[0].len(), 2);
+        }
+
+        assert_eq!(detect_workbook(b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1\0\0"), Some(InputFormat::Xls));
+        assert_eq!(detect_workbook(b"a,b"), None);
+    }
+}
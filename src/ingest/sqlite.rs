@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::{self, Read};
+use rusqlite::{Connection, OpenFlags};
+use rusqlite::types::ValueRef;
+use crate::ingest::InputFormat;
+use crate::ingest::json::{JsonFields, Scalar};
+
+/// the first bytes of every sqlite database
+const MAGIC: &[u8] = b"SQLite format 3\0";
+
+pub fn has_sqlite_magic(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// true when a path should be attached as a database rather than read as a single table, going by
+/// the format given, then the extension and finally the file's first bytes
+pub fn is_database(path: &str, format: Option<InputFormat>) -> bool {
+    match format.or_else(|| InputFormat::from_path(path)) {
+        Some(format) => format == InputFormat::Sqlite,
+        None => {
+            let mut magic = [0; 16];
+
+            File::open(path).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && has_sqlite_magic(&magic)
+        }
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn invalid(e: rusqlite::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// a value as sqlite stored it, blobs are read as hex strings
+fn scalar(value: ValueRef) -> Option<Scalar> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(i) => Some(Scalar::Int(i)),
+        ValueRef::Real(f) => Some(Scalar::Float(f)),
+        ValueRef::Text(text) => Some(Scalar::String(String::from_utf8_lossy(text).into_owned())),
+        ValueRef::Blob(blob) => Some(Scalar::String(blob.iter().map(|b| format!("{:02X}", b)).collect())),
+    }
+}
+
+/// a sqlite database, opened read only
+pub struct Database {
+    connection: Connection,
+}
+
+impl Database {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+            .map_err(invalid)?;
+
+        Ok(Database { connection })
+    }
+
+    /// every table, in order of name. sqlite's own tables are left out.
+    pub fn tables(&self) -> io::Result<Vec<String>> {
+        let mut statement = self.connection
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+            .map_err(invalid)?;
+
+        let names = statement.query_map([], |row| row.get(0)).map_err(invalid)?;
+
+        names.collect::<Result<_, _>>().map_err(invalid)
+    }
+
+    /// every row of a table with the column names it is queried by. Values keep the storage class
+    /// sqlite gave them, so a column is only an int column when every value was stored as one.
+    pub fn export(&self, table: &str) -> io::Result<JsonFields> {
+        let mut statement = self.connection.prepare(&format!("SELECT * FROM {}", quote_identifier(table))).map_err(invalid)?;
+
+        let names: Vec<String> = statement.column_names().iter()
+            .map(|name| name.to_lowercase().replace(".", "_").replace(" ", "_"))
+            .collect();
+        let mut values = vec![vec![]; names.len()];
+
+        let mut rows = statement.query([]).map_err(invalid)?;
+
+        while let Some(row) = rows.next().map_err(invalid)? {
+            for (num, column) in values.iter_mut().enumerate() {
+                column.push(scalar(row.get_ref(num).map_err(invalid)?));
+            }
+        }
+
+        Ok((names, values))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rusqlite::Connection;
+    use crate::ingest::sqlite::{is_database, Database};
+    use crate::ingest::json::Scalar;
+    use crate::ingest::InputFormat;
+
+    #[test]
+    fn values_keep_their_storage_class() {
+        let path = std::env::temp_dir().join(format!("sqlit_values_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let connection = Connection::open(&path).unwrap();
+        connection.execute_batch("CREATE TABLE t(\"Big Id\" integer, amount text, data blob);
+            INSERT INTO t VALUES (9007199254740993, '00042', x'00ff10'), (NULL, 1.5, NULL);").unwrap();
+        drop(connection);
+
+        let database = Database::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(database.tables().unwrap(), vec!["t"]);
+
+        let (names, values) = database.export("t").unwrap();
+        assert_eq!(names, vec!["big_id", "amount", "data"]);
+        assert_eq!(values[0], vec![Some(Scalar::Int(9007199254740993)), None]);
+        // text affinity stores the number as text, the leading zeros stay
+        assert_eq!(values[1], vec![Some(Scalar::String("00042".to_string())), Some(Scalar::String("1.5".to_string()))]);
+        assert_eq!(values[2], vec![Some(Scalar::String("00FF10".to_string())), None]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn databases_by_extension_or_contents() {
+        assert!(is_database("app.sqlite", None));
+        assert!(is_database("data.csv", Some(InputFormat::Sqlite)));
+        assert!(!is_database("tests/data/zips.csv", None));
+        assert!(!is_database("tests/data/archive.dat", None));
+    }
+}
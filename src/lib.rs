@@ -1,5 +1,6 @@
 mod build_column;
 mod converters;
+mod columnar;
 pub mod eval;
 pub mod parser;
 pub mod result;
@@ -8,6 +9,7 @@ pub mod ops;
 pub mod tokenizer;
 pub mod linefeed_io;
 pub mod ingest;
+pub mod output;
 
 
 #[cfg(test)]
@@ -1,37 +1,132 @@
 use linefeed;
+use std::io::{Read, Write, IsTerminal};
 use std::sync::Arc;
 
 use sqlit::linefeed_io::TableCompleter;
 use sqlit::parser::rdp::RecursiveDescentParser;
+use sqlit::parser::ParserNodeType;
+use sqlit::result::SqlResult;
 use sqlit::tokenizer;
 use sqlit::table;
 use sqlit::ops;
 use sqlit::eval;
+use sqlit::output::{self, TableWriter};
+use sqlit::ingest::{LoadOptions, InputFormat, parse_schema};
 
 mod args;
 
-fn main() -> std::io::Result<()> {
+fn completer(store: &table::Store) -> Arc<TableCompleter> {
+    Arc::new(TableCompleter::new(store.meta()))
+}
 
-    // reading command line args
-    let args = args::get();
+/// asks for the columns of each table, showing the first line of its file. An empty answer keeps the file's own.
+/// A file that can't be read is reported and left for loading to fail on.
+fn ask_for_columns(tables: &mut Vec<(String, LoadOptions)>) -> std::io::Result<()> {
+    for (path, options) in tables.iter_mut() {
+        // stdin is read as the table, so nothing can be asked of it, and a folder or glob has no one first line
+        if path == table::STDIN_PATH || table::is_glob(path) || std::path::Path::new(path).is_dir() {
+            continue;
+        }
 
-    // creating our tokenizer
+        // binary files have no first line to show, and keep the columns they were written with
+        if let Some(InputFormat::Parquet) | Some(InputFormat::Arrow) | Some(InputFormat::Sqlite) | Some(InputFormat::Xlsx) | Some(InputFormat::Xls) | Some(InputFormat::Ods)
+            = options.format.or_else(|| InputFormat::from_path(path)) {
+            continue;
+        }
+
+        let first_line = match table::first_line(path, options) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                continue;
+            },
+        };
+
+        loop {
+            print!("{}\n{}\ncolumns (name[:type], ...): ", path, first_line.trim_end());
+            std::io::stdout().flush()?;
+
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer)?;
+
+            if answer.trim().is_empty() {
+                break;
+            }
+
+            match parse_schema(answer.trim()) {
+                Ok(columns) => {
+                    options.columns = Some(columns);
+                    break;
+                },
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_result(table: &table::Table, writer: &dyn TableWriter) -> std::io::Result<()> {
+    // statements such as LOAD return an empty table, nothing worth printing
+    if table.num_columns() == 0 {
+        return Ok(());
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    writer.write(table, &mut out)?;
+    out.flush()
+}
+
+/// reloads the tables whose files changed, saying which. True if any were.
+fn refresh(table_store: &mut table::Store) -> bool {
+    let refreshed = table_store.refresh();
+
+    for result in refreshed.iter() {
+        match result {
+            Ok(done) => eprintln!("{}", done),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    !refreshed.is_empty()
+}
+
+/// runs every statement in the script, stopping at the first error
+fn run_script(script: &str, writer: &dyn TableWriter, ops: &mut ops::OpContext, table_store: &mut table::Store, watch: bool) -> SqlResult<()> {
     let toke = tokenizer::Tokenizer::new();
 
-    // loading tables
-    let mut table_store = table::Store::from_paths(args.table_paths, &args.separator, &args.null_representation)?;
+    for statement in tokenizer::split_statements(script) {
+        if watch {
+            refresh(table_store);
+        }
+
+        if let Some(name) = statement.trim().strip_prefix("\\reload") {
+            table_store.reload(name.trim())?;
+            continue;
+        }
+
+        let parsed = RecursiveDescentParser::new(toke.tokenize(statement)).parse()?;
+        let evaluated = eval::eval(parsed, ops, table_store)?;
+
+        write_result(&evaluated, writer).map_err(|e| sqlit::result::SqlError::io_error(&e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+fn run_prompt(mut writer: Box<dyn TableWriter>, ops: &mut ops::OpContext, table_store: &mut table::Store, watch: bool) -> std::io::Result<()> {
+    // creating our tokenizer
+    let toke = tokenizer::Tokenizer::new();
 
     // setting up io interface
     let io = linefeed::Interface::new("sqlit")?;
 
-    io.set_completer(
-        Arc::new(
-            TableCompleter::new(table_store.list().iter().map(|t| t.meta()).collect())));
+    io.set_completer(completer(table_store));
 
     io.set_prompt("sqlit> ")?;
     io.bind_sequence("clear", linefeed::command::Command::ClearScreen);
-    // get ops
-    let mut ops = ops::OpContext::new();
 
     // loop
     while let linefeed::ReadResult::Input(input) = io.read_line()? {
@@ -41,15 +136,50 @@ fn main() -> std::io::Result<()> {
             io.add_history(input.trim().to_string());
         }
 
+        // .mode <format> changes how results are printed
+        if let Some(format) = input.trim().strip_prefix(".mode") {
+            match output::writer_for(format.trim()) {
+                Some(w) => writer = w,
+                None => println!("unknown format {}, expected one of {:?}", format.trim(), output::FORMATS),
+            }
+
+            continue;
+        }
+
+        // \reload <table> loads a table again from its file
+        if let Some(name) = input.trim().strip_prefix("\\reload") {
+            match table_store.reload(name.trim()) {
+                Ok(names) => println!("reloaded {}", names),
+                Err(e) => println!("{}", e),
+            }
+
+            io.set_completer(completer(table_store));
+            continue;
+        }
+
+        if watch && refresh(table_store) {
+            io.set_completer(completer(table_store));
+        }
+
         let tokens = toke.tokenize(input);
         let mut parser = RecursiveDescentParser::new(tokens);
 
         match parser.parse() {
             Err(e) => println!("{}", e),
             Ok(parsed) => {
-                match eval::eval(parsed, &mut ops, &mut table_store) {
+                // loading or detaching changes what can be completed
+                let tables_changed = match parsed.get_type() {
+                    ParserNodeType::Load | ParserNodeType::Detach => true,
+                    _ => false,
+                };
+
+                match eval::eval(parsed, ops, table_store) {
                     Err(e) => println!("{}", e),
-                    Ok(evaluated) => println!("{}", evaluated)
+                    Ok(evaluated) => write_result(&evaluated, writer.as_ref())?
+                }
+
+                if tables_changed {
+                    io.set_completer(completer(table_store));
                 }
             }
         }
@@ -60,3 +190,58 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
+fn main() -> std::io::Result<()> {
+
+    // reading command line args
+    let args = args::get();
+
+    // loading tables
+    let mut tables = args.tables;
+
+    // a table read from stdin uses up the input queries would otherwise come from
+    if args.execute.is_none() && args.script_path.is_none() && tables.iter().any(|(path, _)| path == table::STDIN_PATH) {
+        eprintln!("reading a table from stdin needs the queries given with -e or -f");
+        std::process::exit(1);
+    }
+
+    if !args.parse_columns {
+        ask_for_columns(&mut tables)?;
+    }
+
+    let mut table_store = table::Store::from_sources(tables).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    // get ops
+    let mut ops = ops::OpContext::new();
+
+    // queries come from -e, -f or a pipe, otherwise we start the prompt
+    let script = if let Some(queries) = args.execute {
+        Some(queries)
+    } else if let Some(path) = args.script_path {
+        Some(std::fs::read_to_string(path)?)
+    } else if !std::io::stdin().is_terminal() {
+        let mut queries = String::new();
+        std::io::stdin().read_to_string(&mut queries)?;
+
+        Some(queries)
+    } else {
+        None
+    };
+
+    // the format was checked against output::FORMATS while parsing args
+    let writer = output::writer_for(&args.format).unwrap();
+
+    match script {
+        Some(script) => {
+            if let Err(e) = run_script(&script, writer.as_ref(), &mut ops, &mut table_store, args.watch) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+
+            Ok(())
+        },
+        None => run_prompt(writer, &mut ops, &mut table_store, args.watch)
+    }
+}
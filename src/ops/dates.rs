@@ -2,7 +2,8 @@ use crate::ops::MapOp;
 use crate::result::{SqlResult, SqlError};
 use crate::table::Column;
 use crate::result::ErrorType::{ Type};
-use chrono::{NaiveDateTime, Datelike};
+use chrono::Datelike;
+use crate::converters::date_time;
 
 pub struct Year{}
 
@@ -13,7 +14,7 @@ impl MapOp for Year {
         match &arguments[0] {
             Column::Dates(d) => {
                 Ok(Column::Ints(d.into_iter().map(|maybe_timestamp| {
-                    maybe_timestamp.map(|timestamp| NaiveDateTime::from_timestamp(timestamp, 0).year() as i64)
+                    maybe_timestamp.and_then(date_time).map(|d| d.year() as i64)
                 }).collect()))
             }
             _ => Err(SqlError::new("year function can only be called on date time", Type))
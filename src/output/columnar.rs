@@ -0,0 +1,26 @@
+use std::io::{Write, Result};
+use crate::columnar::{write_parquet, write_arrow};
+use crate::output::{TableWriter, read_columns};
+use crate::table::Table;
+
+/// a parquet file of a single row group, only chosen by a file's extension
+pub struct Parquet {}
+
+impl TableWriter for Parquet {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        let (columns, _) = read_columns(table)?;
+
+        write_parquet(&columns.iter().map(|(name, c)| (*name, c.as_ref())).collect::<Vec<_>>(), out)
+    }
+}
+
+/// an arrow ipc file, also readable as feather v2
+pub struct Arrow {}
+
+impl TableWriter for Arrow {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        let (columns, _) = read_columns(table)?;
+
+        write_arrow(&columns.iter().map(|(name, c)| (*name, c.as_ref())).collect::<Vec<_>>(), out)
+    }
+}
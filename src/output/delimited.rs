@@ -0,0 +1,50 @@
+use std::io::{Write, Result};
+use crate::output::{TableWriter, cell_text, read_columns};
+use crate::table::Table;
+
+/// csv / tsv following RFC 4180, nulls are written as empty fields
+pub struct Delimited {
+    pub separator: char,
+}
+
+impl Delimited {
+    fn write_field(&self, field: &str, out: &mut dyn Write) -> Result<()> {
+        if field.contains([self.separator, '"', '\n', '\r']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))
+        } else {
+            write!(out, "{}", field)
+        }
+    }
+}
+
+impl TableWriter for Delimited {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        let (columns, rows) = read_columns(table)?;
+
+        for (num, (name, _)) in columns.iter().enumerate() {
+            if num > 0 {
+                write!(out, "{}", self.separator)?;
+            }
+
+            self.write_field(name, out)?;
+        }
+
+        writeln!(out)?;
+
+        for row in 0..rows {
+            for (num, (_, column)) in columns.iter().enumerate() {
+                if num > 0 {
+                    write!(out, "{}", self.separator)?;
+                }
+
+                if let Some(text) = cell_text(column, row) {
+                    self.write_field(&text, out)?;
+                }
+            }
+
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
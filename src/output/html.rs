@@ -0,0 +1,42 @@
+use std::io::{Write, Result};
+use crate::output::{TableWriter, cell_text, read_columns};
+use crate::table::Table;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// a bare <table> element, nulls are left as empty cells
+pub struct Html {}
+
+impl TableWriter for Html {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        let (columns, rows) = read_columns(table)?;
+
+        writeln!(out, "<table>")?;
+        write!(out, "<thead><tr>")?;
+
+        for (name, _) in columns.iter() {
+            write!(out, "<th>{}</th>", escape(name))?;
+        }
+
+        writeln!(out, "</tr></thead>")?;
+        writeln!(out, "<tbody>")?;
+
+        for row in 0..rows {
+            write!(out, "<tr>")?;
+
+            for (_, column) in columns.iter() {
+                write!(out, "<td>{}</td>", escape(&cell_text(column, row).unwrap_or_default()))?;
+            }
+
+            writeln!(out, "</tr>")?;
+        }
+
+        writeln!(out, "</tbody>")?;
+        writeln!(out, "</table>")
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::{Write, Result};
+use std::rc::Rc;
+use crate::converters::date_time;
+use crate::output::{TableWriter, read_columns};
+use crate::table::{Table, Column};
+
+/// writes a cell keeping its type, numbers and booleans stay unquoted
+fn write_value(column: &Column, row: usize, out: &mut dyn Write) -> Result<()> {
+    let value = match column {
+        Column::Strings(s) => s.get(row).cloned().flatten().map(serde_json::Value::from),
+        Column::Ints(i) => i.get(row).cloned().flatten().map(serde_json::Value::from),
+        Column::Floats(f) => f.get(row).cloned().flatten().map(serde_json::Value::from),
+        Column::Booleans(b) => b.get(row).cloned().flatten().map(serde_json::Value::from),
+        Column::Dates(d) => d.get(row).cloned().flatten().and_then(date_time).map(|t| {
+            serde_json::Value::from(t.to_string())
+        }),
+    }.unwrap_or(serde_json::Value::Null);
+
+    write!(out, "{}", value)
+}
+
+fn write_object(columns: &[(&str, Rc<Column>)], row: usize, out: &mut dyn Write) -> Result<()> {
+    write!(out, "{{")?;
+
+    for (num, (name, column)) in columns.iter().enumerate() {
+        if num > 0 {
+            write!(out, ",")?;
+        }
+
+        write!(out, "{}:", serde_json::Value::from(*name))?;
+        write_value(column, row, out)?;
+    }
+
+    write!(out, "}}")
+}
+
+/// an array of objects, one per row
+pub struct Json {}
+
+/// one object per line
+pub struct NdJson {}
+
+impl TableWriter for Json {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        let (columns, rows) = read_columns(table)?;
+
+        write!(out, "[")?;
+
+        for row in 0..rows {
+            if row > 0 {
+                write!(out, ",")?;
+            }
+
+            write_object(&columns, row, out)?;
+        }
+
+        writeln!(out, "]")
+    }
+}
+
+impl TableWriter for NdJson {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        let (columns, rows) = read_columns(table)?;
+
+        for row in 0..rows {
+            write_object(&columns, row, out)?;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::{Write, Result};
+use crate::output::{TableWriter, cell_text, read_columns};
+use crate::table::Table;
+
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// a github flavored markdown table
+pub struct Markdown {}
+
+impl TableWriter for Markdown {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        let (columns, rows) = read_columns(table)?;
+
+        write!(out, "|")?;
+        for (name, _) in columns.iter() {
+            write!(out, " {} |", escape(name))?;
+        }
+
+        write!(out, "\n|")?;
+        for _ in columns.iter() {
+            write!(out, " --- |")?;
+        }
+        writeln!(out)?;
+
+        for row in 0..rows {
+            write!(out, "|")?;
+
+            for (_, column) in columns.iter() {
+                let text = cell_text(column, row).unwrap_or("NULL".to_string());
+                write!(out, " {} |", escape(&text))?;
+            }
+
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
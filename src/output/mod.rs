@@ -0,0 +1,156 @@
+mod columnar;
+mod delimited;
+mod json;
+mod markdown;
+mod html;
+
+use std::io::{Write, Result};
+use std::rc::Rc;
+use crate::converters::date_time;
+use crate::table::{Table, Column};
+
+pub use columnar::{Parquet, Arrow};
+pub use delimited::Delimited;
+pub use json::{Json, NdJson};
+pub use markdown::Markdown;
+pub use html::Html;
+
+/// every format a result can be written in, the first is the default
+pub const FORMATS: [&str; 7] = ["table", "json", "ndjson", "markdown", "csv", "tsv", "html"];
+
+pub trait TableWriter {
+    /// writes the whole table, including a trailing newline
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()>;
+}
+
+/// the text grid shown at the prompt
+pub struct Grid {}
+
+impl TableWriter for Grid {
+    fn write(&self, table: &Table, out: &mut dyn Write) -> Result<()> {
+        // read up front, a file that can't be read is an error rather than a formatting failure
+        table.materialize().map_err(std::io::Error::other)?;
+
+        writeln!(out, "{}", table)
+    }
+}
+
+type NamedRef<'a> = (&'a str, Rc<Column>);
+
+/// the columns of a table and how many rows they have, columns that weren't read yet are read here
+fn read_columns(table: &Table) -> Result<(Vec<NamedRef<'_>>, usize)> {
+    let columns = table.as_columns().map_err(std::io::Error::other)?;
+    let rows = columns.iter().map(|(_, c)| c.len()).max().unwrap_or(0);
+
+    Ok((columns, rows))
+}
+
+/// looks up a writer by its name in FORMATS
+pub fn writer_for(format: &str) -> Option<Box<dyn TableWriter>> {
+    match format {
+        "table" => Some(Box::new(Grid{})),
+        "json" => Some(Box::new(Json{})),
+        "ndjson" => Some(Box::new(NdJson{})),
+        "markdown" | "md" => Some(Box::new(Markdown{})),
+        "csv" => Some(Box::new(Delimited{separator: ','})),
+        "tsv" => Some(Box::new(Delimited{separator: '\t'})),
+        "html" => Some(Box::new(Html{})),
+        _ => None,
+    }
+}
+
+/// picks a writer from a file's extension, IE out.json, out.md or out.parquet, falling back to csv
+pub fn writer_for_file(filename: &str) -> Box<dyn TableWriter> {
+    let extension = filename.rsplit('.').next().unwrap_or("");
+
+    match extension {
+        "jsonl" => Box::new(NdJson{}),
+        "htm" => Box::new(Html{}),
+        "parquet" | "pq" => Box::new(Parquet{}),
+        "arrow" | "feather" | "ipc" => Box::new(Arrow{}),
+        _ => writer_for(extension)
+            .filter(|_| extension != "table")
+            .unwrap_or(Box::new(Delimited{separator: ','})),
+    }
+}
+
+/// the text of a single cell, None for nulls
+fn cell_text(column: &Column, row: usize) -> Option<String> {
+    match column {
+        Column::Strings(s) => s.get(row)?.clone(),
+        Column::Ints(i) => i.get(row)?.map(|v| v.to_string()),
+        Column::Floats(f) => f.get(row)?.map(|v| v.to_string()),
+        Column::Booleans(b) => b.get(row)?.map(|v| v.to_string()),
+        Column::Dates(d) => d.get(row)?.and_then(date_time).map(|v| v.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::output::{writer_for, writer_for_file, FORMATS};
+    use crate::table::{Table, NamedColumn, Column};
+    use std::rc::Rc;
+
+    fn sample() -> Table {
+        let mut t = Table::new();
+
+        t.push(NamedColumn {
+            name: "id".to_string(),
+            column: Rc::new(Column::Ints(vec![Some(1), None])),
+        }, None);
+
+        t.push(NamedColumn {
+            name: "name".to_string(),
+            column: Rc::new(Column::Strings(vec![Some("a \"b\", c".to_string()), Some("<d>".to_string())])),
+        }, None);
+
+        t.push(NamedColumn {
+            name: "ok".to_string(),
+            column: Rc::new(Column::Booleans(vec![Some(true), Some(false)])),
+        }, None);
+
+        t
+    }
+
+    fn render(format: &str) -> String {
+        let mut out = Vec::new();
+        writer_for(format).unwrap().write(&sample(), &mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn every_format_has_a_writer() {
+        for format in FORMATS.iter() {
+            assert!(writer_for(format).is_some());
+        }
+
+        assert!(writer_for("yaml").is_none());
+    }
+
+    #[test]
+    fn json_keeps_types() {
+        assert_eq!(render("json"), "[{\"id\":1,\"name\":\"a \\\"b\\\", c\",\"ok\":true},{\"id\":null,\"name\":\"<d>\",\"ok\":false}]\n");
+        assert_eq!(render("ndjson").lines().count(), 2);
+    }
+
+    #[test]
+    fn text_formats() {
+        assert_eq!(render("csv"), "id,name,ok\n1,\"a \"\"b\"\", c\",true\n,<d>,false\n");
+        assert!(render("markdown").starts_with("| id | name | ok |\n| --- | --- | --- |\n"));
+        assert!(render("html").contains("<td>&lt;d&gt;</td>"));
+    }
+
+    #[test]
+    fn format_from_filename() {
+        let mut out = Vec::new();
+        writer_for_file("out.ndjson").write(&sample(), &mut out).unwrap();
+
+        assert!(String::from_utf8(out).unwrap().starts_with("{\"id\":1"));
+
+        let mut out = Vec::new();
+        writer_for_file("out.parquet").write(&sample(), &mut out).unwrap();
+
+        assert!(out.starts_with(b"PAR1"));
+    }
+}
@@ -27,6 +27,11 @@ pub enum ParserNodeType {
     Into,
     Limit,
     StarOperator,
+    Load,
+    LoadOptions,
+    LoadOption,
+    Detach,
+    FileSource,
 }
 
 #[derive(Clone)]
@@ -61,6 +66,10 @@ impl ParserNode {
         &self.node_type
     }
 
+    pub fn get_children(&self) -> &VecDeque<ParserNode> {
+        &self.children
+    }
+
     pub fn release(self) -> (ParserNodeType, VecDeque<Token>, VecDeque<ParserNode>) {
         (self.node_type, self.tokens, self.children)
     }
@@ -12,6 +12,9 @@ pub struct RecursiveDescentParser {
 
 const PAREN_ERROR: &str = "un-terminated paren";
 
+/// functions that can be used in place of a table name, IE FROM read_csv('x.tsv', sep => '\t')
+const TABLE_FUNCTIONS: [&str; 2] = ["read_csv", "read_json"];
+
 type ParserResult = SqlResult<ParserNode>;
 
 impl RecursiveDescentParser {
@@ -23,7 +26,13 @@ impl RecursiveDescentParser {
     }
 
     pub fn parse(&mut self) -> ParserResult {
-        self.parse_query()
+        if self.next_token_is("load") {
+            self.parse_load()
+        } else if self.next_token_is("detach") {
+            self.parse_detach()
+        } else {
+            self.parse_query()
+        }
     }
 
     fn next_token_is(&self, value: &str) -> bool {
@@ -352,6 +361,15 @@ impl RecursiveDescentParser {
             }
 
             node.add_token(self.get_required_token_by_type(Identifier, "select as table must be followed by table name")?);
+        } else if self.next_token_type_is(Literal) || (self.next_token_type_is(Identifier) && self.next_next_token_is("(")) {
+            node.add_child(self.parse_file_source()?);
+
+            if self.next_token_is("as") {
+                self.next();
+                node.add_token(self.get_required_token_by_type(Identifier, "name required after as")?);
+            } else if self.next_token_type_is(Identifier) {
+                node.add_token(self.next());
+            }
         } else {
             node.add_token(self.get_required_token_by_type(Identifier, "name or (select ...) required after from clause")?);
 
@@ -366,6 +384,43 @@ impl RecursiveDescentParser {
         Ok(node)
     }
 
+    /// 'path/to/file.csv' or read_csv('path/to/file.csv', name => value, ...)
+    fn parse_file_source(&mut self) -> ParserResult {
+        let mut node = ParserNode::new(ParserNodeType::FileSource);
+
+        if self.next_token_type_is(Literal) {
+            node.add_token(self.next());
+
+            return Ok(node);
+        }
+
+        let function = self.get_required_token_by_type(Identifier, "table function required")?;
+
+        if !TABLE_FUNCTIONS.iter().any(|f| function.is(f)) {
+            return Err(SqlError::new(format!("{} cannot be used as a table, expected one of {:?}", function.get_text(), TABLE_FUNCTIONS).as_str(), Syntax));
+        }
+
+        self.get_required_token_by_value("(", "missing opening paren")?;
+
+        node.add_token(self.get_required_token_by_type(Literal, "reading a file requires 'path/to/file.ext'")?);
+        node.add_token(function);
+
+        if self.next_token_is(",") {
+            let mut options = ParserNode::new(ParserNodeType::LoadOptions);
+
+            while self.next_token_is(",") {
+                self.next();
+                options.add_child(self.parse_load_option("=>")?);
+            }
+
+            node.add_child(options);
+        }
+
+        self.get_required_token_by_value(")", PAREN_ERROR)?;
+
+        Ok(node)
+    }
+
     fn parse_where(&mut self) -> ParserResult {
         let mut node = ParserNode::new(Where);
 
@@ -436,6 +491,78 @@ impl RecursiveDescentParser {
 
         Ok(node)
     }
+
+    fn parse_load(&mut self) -> ParserResult {
+        let mut node = ParserNode::new(ParserNodeType::Load);
+
+        self.get_required_token_by_value("load", "load statement must begin with load")?;
+
+        node.add_token(self.get_required_token_by_type(Literal, "loading a file requires 'path/to/file.ext'")?);
+
+        if self.next_token_is("as") {
+            self.next();
+            node.add_token(self.get_required_token_by_type(Identifier, "name required after as")?);
+        }
+
+        if self.next_token_is("with") {
+            node.add_child(self.parse_load_options()?);
+        }
+
+        Ok(node)
+    }
+
+    fn parse_load_options(&mut self) -> ParserResult {
+        let mut node = ParserNode::new(ParserNodeType::LoadOptions);
+
+        self.get_required_token_by_value("with", "load options must begin with with")?;
+        self.get_required_token_by_value("(", "missing opening paren")?;
+
+        loop {
+            node.add_child(self.parse_load_option("=")?);
+
+            if self.next_token_is(",") {
+                self.next();
+            } else {
+                break;
+            }
+        }
+
+        self.get_required_token_by_value(")", PAREN_ERROR)?;
+
+        Ok(node)
+    }
+
+    /// key = value (or key => value in a table function), where key or value may have been
+    /// tokenized as either an identifier or a literal IE null, false
+    fn parse_load_option(&mut self, assignment: &str) -> ParserResult {
+        let mut node = ParserNode::new(ParserNodeType::LoadOption);
+
+        if !(self.next_token_type_is(Identifier) || self.next_token_type_is(Literal)) {
+            return Err(SqlError::new("load option requires a name", Syntax));
+        }
+
+        node.add_token(self.next());
+
+        self.get_required_token_by_value(assignment, format!("option must be of the form name {} value", assignment).as_str())?;
+
+        if !(self.next_token_type_is(Identifier) || self.next_token_type_is(Literal)) {
+            return Err(SqlError::new("load option requires a value", Syntax));
+        }
+
+        node.add_token(self.next());
+
+        Ok(node)
+    }
+
+    fn parse_detach(&mut self) -> ParserResult {
+        let mut node = ParserNode::new(ParserNodeType::Detach);
+
+        self.get_required_token_by_value("detach", "detach statement must begin with detach")?;
+
+        node.add_token(self.get_required_token_by_type(Identifier, "detach requires a table name")?);
+
+        Ok(node)
+    }
 }
 
 #[cfg(test)]
@@ -518,6 +645,34 @@ mod test {
         assert!(parsed.is_ok());
     }
 
+    #[test]
+    fn parse_load() {
+        let t = Tokenizer::new();
+        let query = "load 'logs/*.csv' as logs with (sep = '\\t', null='NA', header=false)";
+        let parsed = RecursiveDescentParser::new(t.tokenize(query.to_string())).parse().unwrap();
+
+        let (type_, tokens, mut children) = parsed.release();
+
+        assert_eq!(type_, ParserNodeType::Load);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(children.pop_front().unwrap().release().2.len(), 3);
+    }
+
+    #[test]
+    fn parse_file_sources() {
+        let t = Tokenizer::new();
+
+        for query in vec!["select * from 'data/2024/sales.csv'",
+                          "select * from 'data/2024/sales.csv' as sales",
+                          "select * from read_csv('x.tsv', sep => '\\t', null => 'NA') x",
+                          "select * from read_json('logs.txt') logs"] {
+            assert!(RecursiveDescentParser::new(t.tokenize(query.to_string())).parse().is_ok());
+        }
+
+        let unknown = "select * from read_parquet('x.tsv')";
+        assert!(RecursiveDescentParser::new(t.tokenize(unknown.to_string())).parse().is_err());
+    }
+
     #[test]
     fn multiple_where() {
         let t = Tokenizer::new();
@@ -4,6 +4,7 @@ use crate::result::{SqlResult, SqlError};
 use crate::result::ErrorType::{Runtime, Type};
 use std::fmt::Display;
 use std::io::Write;
+use crate::converters::date_time;
 
 /// apply block returns a non-column, which makes it useful for general vector operations such as len()
 macro_rules! apply_block {
@@ -161,12 +162,66 @@ impl Column {
         })
     }
 
+    /// the value at idx, count times
+    pub fn repeat(&self, idx: usize, count: usize) -> Self {
+        map_block!(self, v, {
+            std::iter::repeat_n(&v[idx], count).cloned().collect()
+        })
+    }
+
     pub fn select(&self, selections: &Vec<Option<bool>>) -> Self {
         map_block!(self, v, {
             select(v, selections)
         })
     }
 
+    pub fn is_all_null(&self) -> bool {
+        apply_block!(self, v, {
+            v.iter().all(|value| value.is_none())
+        })
+    }
+
+    /// every value written as text, as a column is shown
+    pub fn to_strings(&self) -> Vec<Option<String>> {
+        match self {
+            Column::Dates(d) => d.iter().map(|t| t.and_then(date_time).map(|t| t.to_string())).collect(),
+            _ => apply_block!(self, v, {
+                v.iter().map(|value| value.as_ref().map(|value| value.to_string())).collect()
+            }),
+        }
+    }
+
+    /// appends other, widening the type when the two differ: a column of only nulls takes the
+    /// other's type, ints become floats and anything else becomes strings
+    pub fn promote_and_extend(self, other: Self) -> Self {
+        match (self, other) {
+            (Column::Booleans(mut a), Column::Booleans(b)) => { a.extend(b); Column::Booleans(a) },
+            (Column::Dates(mut a), Column::Dates(b)) => { a.extend(b); Column::Dates(a) },
+            (Column::Floats(mut a), Column::Floats(b)) => { a.extend(b); Column::Floats(a) },
+            (Column::Ints(mut a), Column::Ints(b)) => { a.extend(b); Column::Ints(a) },
+            (Column::Strings(mut a), Column::Strings(b)) => { a.extend(b); Column::Strings(a) },
+            (mut column, other) if other.is_all_null() => {
+                (0..other.len()).for_each(|_| column.push_null());
+                column
+            },
+            (column, other) if column.is_all_null() => {
+                let mut nulls = other.new_empty();
+                (0..column.len()).for_each(|_| nulls.push_null());
+                nulls.promote_and_extend(other)
+            },
+            (Column::Ints(i), Column::Floats(f)) => Column::Floats(i.into_iter().map(|i| i.map(|i| i as f64)).chain(f).collect()),
+            (Column::Floats(mut f), Column::Ints(i)) => {
+                f.extend(i.into_iter().map(|i| i.map(|i| i as f64)));
+                Column::Floats(f)
+            },
+            (column, other) => {
+                let mut strings = column.to_strings();
+                strings.extend(other.to_strings());
+                Column::Strings(strings)
+            },
+        }
+    }
+
     pub fn type_(&self) -> ColumnType {
         match self {
             Column::Booleans(_) => ColumnType::Boolean,
@@ -194,9 +249,23 @@ impl Column {
     }
 }
 
+impl ColumnType {
+    /// reads a type as written in a schema, IE int, float, text, bool, date
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "int" | "integer" | "bigint" => Some(ColumnType::Int),
+            "float" | "double" | "real" | "numeric" => Some(ColumnType::Float),
+            "string" | "text" | "varchar" => Some(ColumnType::String),
+            "bool" | "boolean" => Some(ColumnType::Boolean),
+            "date" | "datetime" | "timestamp" => Some(ColumnType::Date),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::table::Column;
+    use crate::table::{Column, ColumnType};
 
     #[test]
     fn test_order() {
@@ -214,4 +283,11 @@ mod test {
             _ => assert!(false)
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn type_from_name() {
+        assert_eq!(ColumnType::from_name("INT"), Some(ColumnType::Int));
+        assert_eq!(ColumnType::from_name("text"), Some(ColumnType::String));
+        assert_eq!(ColumnType::from_name("blob"), None);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::table::{Partitioned, Table, Column, ColumnType, NamedColumn, TableMeta};
+use crate::build_column::{build_typed_column, ColumnBuilder};
+use crate::ingest::{ColumnSpec, LoadOptions};
+use crate::result::{SqlResult, SqlError};
+use crate::result::ErrorType::Runtime;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// the folder name hive gives a partition whose key is null, IE year=__HIVE_DEFAULT_PARTITION__
+const NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// the folders of a path before the first key=value folder or wildcard, IE events for events/year=*/*.csv
+pub fn partition_root(path: &str) -> PathBuf {
+    Path::new(path).components().take_while(|c| {
+        let folder = c.as_os_str().to_string_lossy();
+        !folder.contains(&['=', '*', '?', '['][..])
+    }).collect()
+}
+
+/// true if any folder a file is in names a key and its value
+pub fn is_partitioned(file: &str) -> bool {
+    !partition_values(file).is_empty()
+}
+
+/// the key=value folders a file is in, IE (year, 2024) and (month, 05) for events/year=2024/month=05/part.csv
+fn partition_values(file: &str) -> Vec<(String, String)> {
+    Path::new(file).parent().map(|folders| folders.components().filter_map(|c| {
+        let folder = c.as_os_str().to_string_lossy();
+        let (key, value) = folder.split_once('=')?;
+
+        if key.is_empty() {
+            None
+        } else {
+            Some((key.to_string(), unescape(value)))
+        }
+    }).collect()).unwrap_or_default()
+}
+
+/// hive escapes characters that can't be in a folder name, IE / is written as %2F
+fn unescape(value: &str) -> String {
+    let raw = value.as_bytes();
+    let mut bytes = vec![];
+    let mut at = 0;
+
+    while at < raw.len() {
+        match (raw[at], value.get(at + 1..at + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                at += 3;
+            },
+            (byte, _) => {
+                bytes.push(byte);
+                at += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// every file under a folder in order, leaving out hidden files, markers such as _SUCCESS and
+/// schema files, which are read along with their data
+pub fn list_files(folder: &Path) -> std::io::Result<Vec<String>> {
+    let mut paths = std::fs::read_dir(folder)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<PathBuf>>>()?;
+
+    paths.sort();
+
+    let mut files = vec![];
+
+    for path in paths {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+        if name.starts_with('.') || name.starts_with('_') {
+            continue;
+        }
+
+        if path.is_dir() {
+            files.extend(list_files(&path)?);
+        } else if path.extension().is_none_or(|e| e != "schema") {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+
+    Ok(files)
+}
+
+impl Partitioned {
+    /// groups files by the values of the folders they are in, every file has to be under the same keys.
+    /// Each key becomes a column, typed from its values unless its type is given in the options.
+    pub fn new(alias: String, files: Vec<String>, options: &LoadOptions) -> SqlResult<Self> {
+        let mut names: Option<Vec<String>> = None;
+        let mut partitions: Vec<(Vec<String>, Vec<String>)> = vec![];
+
+        for file in files {
+            let (keys, values): (Vec<String>, Vec<String>) = partition_values(&file).into_iter().unzip();
+
+            match &names {
+                Some(names) if names != &keys => {
+                    return Err(SqlError::new(format!("{} is not partitioned by {}, like the files before it", file, names.join(", ")).as_str(), Runtime));
+                },
+                Some(_) => {},
+                None => names = Some(keys),
+            }
+
+            match partitions.iter_mut().find(|(v, _)| v == &values) {
+                Some((_, files)) => files.push(file),
+                None => partitions.push((values, vec![file])),
+            }
+        }
+
+        let names = names.ok_or(SqlError::look_up_error(&alias, "file system"))?;
+
+        let first = &partitions[0].1[0];
+        let header = Table::from_file(first, options).map_err(|e| SqlError::io_error(format!("{}: {}", first, e).as_str()))?;
+
+        let mut keys = Table::new().with_new_alias(alias.clone());
+
+        for (num, name) in names.iter().enumerate() {
+            let declared = options.types.iter().rev().find(|c| &c.name == name).and_then(|c| c.column_type.clone());
+            let mut builder = ColumnBuilder::with_formats(declared, &options.formats);
+
+            builder.push(&partitions.iter().map(|(values, _)| {
+                Some(values[num].as_str()).filter(|&value| value != NULL_PARTITION)
+            }).collect::<Vec<_>>(), &options.null);
+
+            keys.push(NamedColumn { name: name.clone(), column: Rc::new(builder.finish(&options.null).0) }, None);
+        }
+
+        Ok(Partitioned {
+            alias,
+            options: options.clone(),
+            keys,
+            files: partitions.into_iter().map(|(_, files)| files).collect(),
+            header,
+        })
+    }
+
+    pub fn alias(&self) -> String {
+        self.alias.clone()
+    }
+
+    /// one row per partition, with a column for each key
+    pub fn keys(&self) -> &Table {
+        &self.keys
+    }
+
+    fn is_key(&self, name: &str) -> bool {
+        self.keys.column_names.iter().any(|key| key == name)
+    }
+
+    /// reads the files of the partitions marked in keep, by their row in keys, into one table with the
+    /// columns whose names pass wanted. The files of the other partitions are never opened.
+    pub fn read<F: Fn(&str) -> bool>(&self, keep: &[bool], wanted: F) -> SqlResult<Table> {
+        // a key is taken from the folder, even if the files have a column of the same name
+        let in_files = |name: &str| wanted(name) && !self.is_key(name);
+        let header = self.header.project(in_files);
+        let keys = self.keys.project(&wanted);
+
+        let mut options = self.options.clone();
+
+        let columns = loop {
+            let mut columns: Vec<Option<Column>> = vec![None; header.num_columns() + keys.num_columns()];
+            let mut promoted = vec![];
+
+            for (partition, files) in self.files.iter().enumerate().filter(|(p, _)| keep.get(*p) == Some(&true)) {
+                for file in files {
+                    let table = Table::from_file(file, &options)
+                        .map_err(|e| SqlError::io_error(format!("{}: {}", file, e).as_str()))?
+                        .project(in_files);
+
+                    if table.column_names != header.column_names {
+                        return Err(SqlError::new(format!("{} does not have the same columns as {}", file, self.files[0][0]).as_str(), Runtime));
+                    }
+
+                    table.materialize()?;
+
+                    let rows = table.len()?;
+                    let read = table.columns.iter().map(|c| Ok(c.try_get()?.as_ref().clone()))
+                        .chain(keys.columns.iter().map(|c| Ok(c.try_get()?.repeat(partition, rows))))
+                        .collect::<SqlResult<Vec<Column>>>()?;
+
+                    for (num, (column, next)) in columns.iter_mut().zip(read).enumerate() {
+                        *column = Some(match column.take() {
+                            Some(column) => {
+                                let was = column.type_();
+                                let column = column.promote_and_extend(next);
+
+                                if num < header.num_columns() && was != ColumnType::String && column.type_() == ColumnType::String {
+                                    promoted.push(header.column_names[num].clone());
+                                }
+
+                                column
+                            },
+                            None => next,
+                        });
+                    }
+                }
+            }
+
+            if promoted.is_empty() {
+                break columns;
+            }
+
+            // files read before are only known as values, read as strings they are kept as they were written
+            options.types.extend(promoted.into_iter().map(|name| ColumnSpec { name, column_type: Some(ColumnType::String) }));
+        };
+
+        let mut table = Table::new().with_new_alias(self.alias.clone());
+        let names = header.column_names.iter().zip(header.columns.iter()).chain(keys.column_names.iter().zip(keys.columns.iter()));
+
+        for ((name, slot), column) in names.zip(columns) {
+            // nothing was read when every partition was ruled out
+            let column = column.unwrap_or_else(|| build_typed_column(vec![], "", &slot.type_()).0);
+
+            table.push(NamedColumn { name: name.clone(), column: Rc::new(column) }, None);
+        }
+
+        Ok(table)
+    }
+
+    pub fn meta(&self) -> TableMeta {
+        let mut columns = self.header.meta().columns.into_iter()
+            .filter(|(_, name, _)| !self.is_key(name))
+            .map(|(_, name, column_type)| (self.alias.clone(), name, column_type))
+            .collect::<Vec<_>>();
+
+        columns.extend(self.keys.meta().columns);
+
+        TableMeta { columns, length: 0, alias: self.alias.clone() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::table::impl_partitioned::{partition_root, partition_values, unescape};
+    use std::path::PathBuf;
+
+    #[test]
+    fn values_from_folders() {
+        assert_eq!(partition_values("events/year=2024/month=05/part.csv"),
+                   vec![("year".to_string(), "2024".to_string()), ("month".to_string(), "05".to_string())]);
+        assert_eq!(partition_values("events/part.csv"), vec![]);
+        assert_eq!(unescape("a%2Fb%zz"), "a/b%zz");
+
+        assert_eq!(partition_root("events/year=2024/month=05/*.csv"), PathBuf::from("events"));
+        assert_eq!(partition_root("data/events/*/*.csv"), PathBuf::from("data/events"));
+        assert_eq!(partition_root("data/events"), PathBuf::from("data/events"));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::table::{Table, Slot, Source, Column, ColumnType};
+use crate::table::impl_table::{decode, open_delimited, read_header, read_file_columns, split_records, declared_types};
+use crate::build_column::ColumnBuilder;
+use crate::ingest::{Compression, LoadReport};
+use crate::result::{SqlResult, SqlError};
+use std::fs::File;
+use std::io::{BufRead, Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+impl Slot {
+    pub (super) fn loaded(column: Rc<Column>) -> Self {
+        Slot::Loaded(column)
+    }
+
+    pub (super) fn unread(source: Rc<Source>, index: usize) -> Self {
+        Slot::File(source, index)
+    }
+
+    /// the column if it was read
+    pub (super) fn read(&self) -> Option<Rc<Column>> {
+        match self {
+            Slot::Loaded(column) => Some(column.clone()),
+            Slot::File(source, index) => source.columns.borrow()[*index].clone(),
+        }
+    }
+
+    /// the column, read from its file if it wasn't yet
+    pub (super) fn try_get(&self) -> SqlResult<Rc<Column>> {
+        match self {
+            Slot::Loaded(column) => Ok(column.clone()),
+            Slot::File(source, index) => {
+                source.fill(&[*index]).map_err(|e| SqlError::io_error(format!("{}: {}", source.path, e).as_str()))?;
+
+                Ok(self.read().expect("a column is kept once it is read"))
+            },
+        }
+    }
+
+    /// the type of the column, or the type guessed for it while it hasn't been read
+    pub (super) fn type_(&self) -> ColumnType {
+        match (self.read(), self) {
+            (Some(column), _) => column.type_(),
+            (None, Slot::File(source, index)) => source.types[*index].clone(),
+            (None, Slot::Loaded(_)) => unreachable!("a loaded column is always read"),
+        }
+    }
+}
+
+impl Source {
+    /// reads the columns at the given positions that weren't read yet, with one pass over the file. When the
+    /// file changed since columns were read, they are read again along with them so every column has the same rows.
+    pub (super) fn fill(&self, indices: &[usize]) -> std::io::Result<()> {
+        let mut wanted: Vec<usize> = indices.iter().cloned().filter(|&index| self.columns.borrow()[index].is_none()).collect();
+
+        if wanted.is_empty() {
+            return Ok(());
+        }
+
+        let modified = std::fs::metadata(&self.path)?.modified()?;
+
+        if modified != self.modified.get() {
+            let (reader, _) = open_delimited(&self.path, &self.options)?;
+
+            if read_header(reader, &self.path, self.alias.clone(), &self.options)?.column_names != self.column_names {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "the columns of the file changed since it was loaded, load it again"));
+            }
+
+            wanted.extend(self.columns.borrow().iter().enumerate().filter(|(_, c)| c.is_some()).map(|(index, _)| index));
+            wanted.sort_unstable();
+            wanted.dedup();
+            self.reported.set(false);
+            self.lines.set(None);
+        }
+
+        let report = !self.reported.replace(true);
+        let table = read_file_columns(&self.path, self.alias.clone(), &self.options, Some(&wanted), report)?;
+        let mut columns = self.columns.borrow_mut();
+
+        for (index, slot) in wanted.into_iter().zip(table.columns) {
+            columns[index] = slot.read();
+        }
+
+        self.modified.set(modified);
+
+        Ok(())
+    }
+
+    /// the lines of the file before the offset, the records that were read plus any that were blank or rejected
+    fn lines_before(&self, offset: u64) -> std::io::Result<usize> {
+        if let Some(lines) = self.lines.get() {
+            return Ok(lines);
+        }
+
+        let mut reader = std::io::BufReader::new(File::open(&self.path)?.take(offset));
+        let mut lines = 0;
+
+        loop {
+            let buffer = reader.fill_buf()?;
+
+            if buffer.is_empty() {
+                break;
+            }
+
+            lines += buffer.iter().filter(|&&b| b == b'\n').count();
+            let read = buffer.len();
+            reader.consume(read);
+        }
+
+        Ok(lines)
+    }
+}
+
+impl Table {
+    /// the table with the records added to the end of its file, between the offsets from and to, and how
+    /// many there were. Columns that were read get the new rows, the rest are read from the file as it is
+    /// now once a query uses them. None when the table has to be loaded again instead: it wasn't read from
+    /// a single uncompressed delimited file, its header changed, the file changed again while it was read
+    /// or the new rows turn a column that was read into strings.
+    pub fn with_appended(&self, from: u64, to: u64) -> std::io::Result<Option<(Table, usize)>> {
+        let source = match self.columns.first() {
+            Some(Slot::File(source, _)) => source.clone(),
+            _ => return Ok(None),
+        };
+
+        let whole_file = self.columns.iter().enumerate().all(|(num, slot)| {
+            matches!(slot, Slot::File(other, index) if Rc::ptr_eq(other, &source) && *index == num)
+        });
+
+        let mut file = File::open(&source.path)?;
+
+        if !whole_file || Compression::from_path(&source.path).is_some() || Compression::detect(&mut file)?.is_some() {
+            return Ok(None);
+        }
+
+        let (reader, dialect) = open_delimited(&source.path, &source.options)?;
+        let fresh = read_header(reader, &source.path, source.alias.clone(), &source.options)?;
+
+        if fresh.column_names != self.column_names {
+            return Ok(None);
+        }
+
+        let mut raw = vec![];
+        file.seek(SeekFrom::Start(from))?;
+        file.take(to - from).read_to_end(&mut raw)?;
+
+        let mut text = String::new();
+        decode(raw.as_slice(), &source.options)?.read_to_string(&mut text)?;
+
+        // the records are counted with the first column when none were read
+        let loaded: Vec<usize> = (0..self.columns.len()).filter(|&num| self.columns[num].read().is_some()).collect();
+        let wanted = if loaded.is_empty() { vec![0] } else { loaded };
+
+        let lines_before = source.lines_before(from)?;
+        let (raw_columns, rejects, padded) = split_records(&text, lines_before, &dialect, self.columns.len(), &wanted, source.options.pad_missing);
+        let added = raw_columns.first().map_or(0, |c| c.len());
+
+        let mut report = LoadReport::new(&self.alias, self.columns.len(), None);
+        report.padded = padded;
+
+        for reject in rejects {
+            report.reject(reject)?;
+        }
+
+        if !report.is_empty() {
+            eprintln!("Warning: {}", report);
+        }
+
+        let fresh_source = match fresh.columns.first() {
+            Some(Slot::File(fresh_source, _)) => fresh_source.clone(),
+            _ => return Ok(None),
+        };
+
+        // written to again while it was read, what was read may not line up with the new source
+        if fresh_source.modified.get() != std::fs::metadata(&source.path)?.modified()? {
+            return Ok(None);
+        }
+
+        fresh_source.lines.set(Some(lines_before + text.matches('\n').count()));
+
+        let declared = declared_types(&source.options, &self.column_names);
+        let mut appended = raw_columns.into_iter();
+
+        // columns that were read get the new rows, the fresh source keeps them as read
+        for (num, slot) in self.columns.iter().enumerate() {
+            if let Some(column) = slot.read() {
+                let mut builder = ColumnBuilder::with_formats(declared[num].clone(), &source.options.formats);
+                builder.push(&appended.next().unwrap_or_default(), &source.options.null);
+
+                let extended = column.as_ref().clone().promote_and_extend(builder.finish(&source.options.null).0);
+
+                // the rows read before are only known as values, as strings they have to be read again
+                if extended.type_() == ColumnType::String && column.type_() != ColumnType::String {
+                    return Ok(None);
+                }
+
+                fresh_source.columns.borrow_mut()[num] = Some(Rc::new(extended));
+            }
+        }
+
+        Ok(Some((Table {
+            alias: self.alias.clone(),
+            column_names: self.column_names.clone(),
+            column_map: self.column_map.clone(),
+            columns: fresh.columns,
+        }, added)))
+    }
+}
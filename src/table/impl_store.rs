@@ -1,49 +1,375 @@
-use crate::table::{Store, Table};
+use crate::table::{Store, Table, TableMeta, CachedFile, Origin, Partitioned, STDIN_PATH};
+use crate::table::impl_partitioned::{partition_root, is_partitioned, list_files};
 use crate::result::{SqlResult, SqlError};
 use std::collections::HashMap;
-use crate::result::ErrorType::{Lookup};
-use std::io;
-use crate::ingest::SepFinder;
-use std::rc::Rc;
+use crate::result::ErrorType::{Lookup, Runtime};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::cell::RefCell;
+use std::time::SystemTime;
+use std::path::Path;
+use crate::ingest::{LoadOptions, Database, is_database, is_workbook};
+
+/// true if the path should be expanded into multiple files, IE logs/*.csv
+pub fn is_glob(path: &str) -> bool {
+    path.contains(&['*', '?', '['][..])
+}
+
+fn expand_glob(pattern: &str) -> SqlResult<Vec<String>> {
+    let paths = glob::glob(pattern)
+        .map_err(|e| SqlError::new(format!("invalid pattern {}: {}", pattern, e).as_str(), Runtime))?
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+
+    if paths.is_empty() {
+        Err(SqlError::look_up_error(pattern, "file system"))
+    } else {
+        Ok(paths)
+    }
+}
+
+/// how much of the end of a file is kept to tell whether it was only added to since it was loaded
+const END_BYTES: u64 = 256;
+
+/// the latest modification time and the total size of the files a path loads
+fn stamp(path: &str) -> SqlResult<(SystemTime, u64)> {
+    let files = if is_glob(path) {
+        expand_glob(path)?
+    } else if Path::new(path).is_dir() {
+        list_files(Path::new(path)).map_err(|e| SqlError::io_error(format!("{}: {}", path, e).as_str()))?
+    } else {
+        vec![path.to_string()]
+    };
+
+    files.iter().try_fold((SystemTime::UNIX_EPOCH, 0), |(modified, length), file| {
+        std::fs::metadata(file).and_then(|m| Ok((modified.max(m.modified()?), length + m.len())))
+            .map_err(|e| SqlError::io_error(format!("{}: {}", file, e).as_str()))
+    })
+}
+
+/// the bytes of a file just before length, nothing for a folder or pattern
+fn last_bytes(path: &str, length: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+
+    if Path::new(path).is_file() {
+        let mut file = std::fs::File::open(path)?;
+        file.seek(SeekFrom::Start(length - length.min(END_BYTES)))?;
+        file.take(length.min(END_BYTES)).read_to_end(&mut bytes)?;
+    }
+
+    Ok(bytes)
+}
+
+impl Origin {
+    fn new(path: &str, alias: Option<&str>, options: &LoadOptions) -> SqlResult<Self> {
+        let (modified, length) = stamp(path)?;
+        let end = last_bytes(path, length).map_err(|e| SqlError::io_error(format!("{}: {}", path, e).as_str()))?;
+
+        Ok(Origin { path: path.to_string(), alias: alias.map(|a| a.to_string()), options: options.clone(), modified, length, end })
+    }
+
+    /// true if the file still starts with what was loaded, and that ended with a whole record
+    fn only_added_to(&self) -> io::Result<bool> {
+        Ok(self.end.ends_with(b"\n") && last_bytes(&self.path, self.length)? == self.end)
+    }
+}
 
 impl Store {
 
     pub fn new() -> Self {
         Store {
-            tables: HashMap::new()
+            tables: HashMap::new(),
+            partitioned: HashMap::new(),
+            files: RefCell::new(HashMap::new()),
+            origins: HashMap::new(),
         }
     }
-    pub fn from_paths(csv_paths: Vec<String>, separator: &Box<dyn SepFinder>, null: &str) -> io::Result<Self> {
+    pub fn from_paths(csv_paths: Vec<String>, options: &LoadOptions) -> io::Result<Self> {
 
         csv_paths.into_iter().map(|path| {
-            Table::from_file(path.as_str(), separator, null).map(|t| {
+            Table::from_file(path.as_str(), options).map(|t| {
                 (t.alias(), t)
             })
-        }).collect::<std::io::Result<HashMap<String, Table>>>().map(|tables| Self {tables})
+        }).collect::<std::io::Result<HashMap<String, Table>>>().map(|tables| Self {
+            tables,
+            partitioned: HashMap::new(),
+            files: RefCell::new(HashMap::new()),
+            origins: HashMap::new(),
+        })
+    }
+
+    /// Loads every path given on the command line with its own options, IE a name or separator
+    pub fn from_sources(sources: Vec<(String, LoadOptions)>) -> SqlResult<Self> {
+        let mut store = Self::new();
+
+        for (path, options) in sources {
+            store.load(&path, None, &options)?;
+        }
+
+        Ok(store)
+    }
+
+    /// Reads a file referenced directly in a query, IE SELECT * FROM 'data/sales.csv', without
+    /// registering it. Files are cached by path and only re-read once their modification time or the
+    /// options they are read with change.
+    /// stdin can only be read once, so it stays cached for the rest of the session.
+    pub fn read_file(&self, path: &str, options: &LoadOptions) -> SqlResult<Table> {
+        let modified = if path == STDIN_PATH {
+            SystemTime::UNIX_EPOCH
+        } else {
+            std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .map_err(|e| SqlError::io_error(format!("{}: {}", path, e).as_str()))?
+        };
+
+        if let Some(cached) = self.files.borrow().get(path) {
+            if cached.modified == modified && cached.options.same_as(options) {
+                return Ok(cached.table.clone());
+            }
+        }
+
+        let table = Table::from_file(path, options).map_err(|e| SqlError::io_error(format!("{}: {}", path, e).as_str()))?;
+
+        self.files.borrow_mut().insert(path.to_string(), CachedFile {
+            modified, options: options.clone(), table: table.clone()
+        });
+
+        Ok(table)
+    }
+
+    /// Reads a file, or every file matching a glob pattern, into the store and returns the
+    /// alias it was registered under. Files matched by a pattern are unioned into one table.
+    /// An explicit alias wins over the name in the options. A sqlite database is attached instead,
+    /// and every sheet of a workbook is loaded unless one is picked with the sheet option. A folder,
+    /// or a pattern matching files in key=value folders, is loaded as a partitioned table. Every table
+    /// but stdin remembers where it came from, for Store::reload.
+    pub fn load(&mut self, path: &str, alias: Option<&str>, options: &LoadOptions) -> SqlResult<String> {
+        let names = self.load_names(path, alias, options)?;
+
+        if path != STDIN_PATH {
+            let origin = Origin::new(path, alias, options)?;
+
+            for name in names.iter() {
+                self.origins.insert(name.clone(), origin.clone());
+            }
+        }
+
+        Ok(names.join(", "))
+    }
+
+    fn load_names(&mut self, path: &str, alias: Option<&str>, options: &LoadOptions) -> SqlResult<Vec<String>> {
+        let alias = alias.or(options.name.as_deref());
+
+        if path != STDIN_PATH && (Path::new(path).is_dir() || is_glob(path) && expand_glob(path)?.iter().any(|p| is_partitioned(p))) {
+            return self.load_partitioned(path, alias, options).map(|name| vec![name]);
+        }
+
+        if path != STDIN_PATH && !is_glob(path) && is_database(path, options.format) {
+            return self.attach(path, alias, options);
+        }
+
+        if path != STDIN_PATH && !is_glob(path) && options.sheet.is_none() && is_workbook(path, options.format) {
+            return self.load_sheets(path, alias, options);
+        }
+
+        let table = if is_glob(path) {
+            let alias = alias.ok_or(SqlError::new("loading a pattern requires a table name, IE LOAD 'logs/*.csv' AS logs", Runtime))?;
+
+            let mut tables = expand_glob(path)?.into_iter().map(|p| {
+                Table::from_file(p.as_str(), options).map_err(|e| SqlError::io_error(format!("{}: {}", p, e).as_str()))
+            }).collect::<SqlResult<Vec<Table>>>()?.into_iter();
+
+            let first = tables.next().unwrap();
+
+            tables.try_fold(first, |acc, next| {
+                if acc.column_names != next.column_names {
+                    Err(SqlError::new("files matched by pattern do not share the same columns", Runtime))
+                } else {
+                    acc.merge(&next)
+                }
+            })?.with_new_alias(alias.to_string())
+        } else {
+            let table = Table::from_file(path, options).map_err(|e| SqlError::io_error(format!("{}: {}", path, e).as_str()))?;
+
+            match alias {
+                Some(alias) => table.with_new_alias(alias.to_string()),
+                None => table,
+            }
+        };
+
+        let alias = table.alias();
+        self.set(table);
+
+        Ok(vec![alias])
+    }
+
+    /// Loads a table again from where it was loaded, along with the tables loaded with it, IE the other
+    /// tables of a database. Returns the names they were loaded as.
+    pub fn reload(&mut self, name: &str) -> SqlResult<String> {
+        let origin = self.origins.get(name)
+            .ok_or(SqlError::new(format!("{} was not loaded from a file", name).as_str(), Lookup))?;
+
+        let (path, alias, options) = (origin.path.clone(), origin.alias.clone(), origin.options.clone());
+
+        self.load(&path, alias.as_deref(), &options)
+    }
+
+    /// Brings every table whose files changed since they were loaded up to date, see --watch. When a file
+    /// was only added to just its new records are read. Says what was done for each table that changed,
+    /// IE sales: 3 new rows.
+    pub fn refresh(&mut self) -> Vec<SqlResult<String>> {
+        let mut names: Vec<String> = self.origins.keys().cloned().collect();
+        names.sort();
+
+        names.iter().filter_map(|name| self.refresh_table(name).transpose()).collect()
+    }
+
+    fn refresh_table(&mut self, name: &str) -> SqlResult<Option<String>> {
+        // tables loaded along with one that was reloaded are already up to date
+        let origin = match self.origins.get(name) {
+            Some(origin) => origin,
+            None => return Ok(None),
+        };
+
+        let (modified, length) = stamp(&origin.path)?;
+
+        if modified == origin.modified && length == origin.length {
+            return Ok(None);
+        }
+
+        let appended = match self.tables.get(name) {
+            Some(table) if length > origin.length && origin.only_added_to().unwrap_or(false) => {
+                table.with_appended(origin.length, length).map_err(|e| SqlError::io_error(format!("{}: {}", origin.path, e).as_str()))?
+            },
+            _ => None,
+        };
+
+        match appended {
+            Some((table, added)) => {
+                let end = last_bytes(&origin.path, length).map_err(|e| SqlError::io_error(format!("{}: {}", origin.path, e).as_str()))?;
+
+                if let Some(origin) = self.origins.get_mut(name) {
+                    origin.modified = modified;
+                    origin.length = length;
+                    origin.end = end;
+                }
+
+                self.tables.insert(name.to_string(), table);
+
+                Ok(Some(format!("{}: {} new rows", name, added)))
+            },
+            None => self.reload(name).map(|names| Some(format!("{}: reloaded", names))),
+        }
+    }
+
+    /// Registers every file under a folder, or matching a pattern, as one table with a column for each
+    /// key=value folder, IE events/year=2024/month=05/*.csv. The files are only read by the queries
+    /// that use them. Without an alias the table is named after the folder the partitions are in.
+    pub fn load_partitioned(&mut self, path: &str, alias: Option<&str>, options: &LoadOptions) -> SqlResult<String> {
+        let files = if is_glob(path) {
+            expand_glob(path)?
+        } else {
+            list_files(Path::new(path)).map_err(|e| SqlError::io_error(format!("{}: {}", path, e).as_str()))?
+        };
+
+        let root = partition_root(path);
+        let name = root.canonicalize().ok()
+            .and_then(|root| root.file_name().map(|name| name.to_string_lossy().replace(".", "_")));
+
+        let alias = alias.map(|alias| alias.to_string()).or(name)
+            .ok_or(SqlError::new(format!("could not name the table for {}, give it one with AS", path).as_str(), Runtime))?;
+
+        let table = Partitioned::new(alias.clone(), files, options)?;
+
+        self.tables.remove(&alias);
+        self.partitioned.insert(alias.clone(), table);
+
+        Ok(alias)
+    }
+
+    /// Loads every table of a sqlite database under its own name, or as alias_table when an alias
+    /// is given so two databases can share table names. Returns the names the tables were loaded as.
+    pub fn attach(&mut self, path: &str, alias: Option<&str>, options: &LoadOptions) -> SqlResult<Vec<String>> {
+        let error = |e: io::Error| SqlError::io_error(format!("{}: {}", path, e).as_str());
+
+        if !Path::new(path).is_file() {
+            return Err(SqlError::look_up_error(path, "file system"));
+        }
+
+        let database = Database::open(path).map_err(error)?;
+        let mut names = vec![];
+
+        for table in database.tables().map_err(error)? {
+            let name = match alias {
+                Some(alias) => format!("{}_{}", alias, table),
+                None => table.clone(),
+            }.to_lowercase().replace(".", "_").replace(" ", "_");
+
+            self.set(Table::from_database(&table, &database, name.clone(), options).map_err(error)?);
+            names.push(name);
+        }
+
+        Ok(names)
+    }
+
+    /// Loads every sheet of a workbook as file_sheet, or alias_sheet when an alias is given. Returns
+    /// the names the sheets were loaded as.
+    pub fn load_sheets(&mut self, path: &str, alias: Option<&str>, options: &LoadOptions) -> SqlResult<Vec<String>> {
+        let tables = Table::from_workbook(path, alias, options).map_err(|e| SqlError::io_error(format!("{}: {}", path, e).as_str()))?;
+
+        Ok(tables.into_iter().map(|table| {
+            let name = table.alias();
+            self.set(table);
+
+            name
+        }).collect())
+    }
+
+    /// Removes a table from the store, a partitioned table comes back without any of its rows
+    pub fn detach(&mut self, alias: &str) -> SqlResult<Table> {
+        self.origins.remove(alias);
+
+        match self.partitioned.remove(alias) {
+            Some(partitioned) => partitioned.read(&[], |_| true),
+            None => self.tables.remove(alias).ok_or(SqlError::look_up_error(alias, "store")),
+        }
     }
 
     pub fn get(&self, alias: &str) -> SqlResult<&Table> {
         self.tables.get(alias).ok_or(SqlError::new(format!("alias {} not found in store", alias).as_str(), Lookup))
     }
 
+    /// The partitioned table registered under alias, its files are read with Partitioned::read
+    pub fn partitioned(&self, alias: &str) -> Option<&Partitioned> {
+        self.partitioned.get(alias)
+    }
+
+    /// Registers a table under its alias, it no longer reloads from the file a table of the same name was loaded from
     pub fn set(&mut self, table: Table) {
+        self.origins.remove(table.alias_ref());
+        self.partitioned.remove(table.alias_ref());
         self.tables.insert(table.alias(), table);
     }
 
     pub fn list(&self) -> Vec<&Table> {
         self.tables.values().collect()
     }
+
+    /// The columns of every table, partitioned ones included
+    pub fn meta(&self) -> Vec<TableMeta> {
+        self.tables.values().map(|t| t.meta()).chain(self.partitioned.values().map(|p| p.meta())).collect()
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::table::Store;
-    use crate::result::SqlResult;
-    use crate::ingest::{CsvFinder, SepFinder};
+    use crate::table::{Store, ColumnType};
+    use crate::ingest::{LoadOptions, parse_types};
 
     #[test]
     fn test_get() -> std::io::Result<()>{
-        let s = Store::from_paths(vec!["tests/data/nba.games.stats.csv".to_string()], &(Box::new(CsvFinder{}) as Box<dyn SepFinder>), "null")?;
+        let s = Store::from_paths(vec!["tests/data/nba.games.stats.csv".to_string()], &LoadOptions::default())?;
 
 
         match s.get("nba_games_stats") {
@@ -53,4 +379,174 @@ mod test {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_load_and_detach() {
+        let mut s = Store::new();
+        let mut options = LoadOptions::default();
+        options.set("sep", "\\t").unwrap();
+
+        let alias = s.load("tests/data/*.tsv", Some("tsvs"), &options).unwrap();
+
+        assert_eq!(alias, "tsvs");
+        assert_eq!(s.get("tsvs").unwrap().len().unwrap(), 1);
+
+        assert!(s.detach("tsvs").is_ok());
+        assert!(s.get("tsvs").is_err());
+        assert!(s.detach("tsvs").is_err());
+    }
+
+    #[test]
+    fn test_from_sources() {
+        let mut named = LoadOptions::default();
+        named.set_all("sep=tab,name=named").unwrap();
+
+        let s = Store::from_sources(vec![
+            ("tests/data/test.tsv".to_string(), named),
+            ("tests/data/test.tsv".to_string(), LoadOptions::default()),
+        ]).unwrap();
+
+        assert!(s.get("named").is_ok());
+        assert!(s.get("test").is_ok());
+        assert!(Store::from_sources(vec![("tests/data/missing.csv".to_string(), LoadOptions::default())]).is_err());
+    }
+
+    #[test]
+    fn test_attach_sqlite() {
+        let mut s = Store::new();
+
+        assert_eq!(s.load("tests/data/app.db", None, &LoadOptions::default()).unwrap(), "customers, orders");
+        assert_eq!(s.attach("tests/data/app.db", Some("app"), &LoadOptions::default()).unwrap(), vec!["app_customers", "app_orders"]);
+
+        // columns take the storage class of their values, sqlite has no booleans or dates
+        let customers = s.get("customers").unwrap();
+        assert_eq!(customers.len().unwrap(), 3);
+        assert_eq!(customers.column_search("id").unwrap().type_(), ColumnType::Int);
+        assert_eq!(customers.column_search("zip").unwrap().type_(), ColumnType::String);
+        assert_eq!(customers.column_search("active").unwrap().type_(), ColumnType::Int);
+        assert_eq!(customers.column_search("joined").unwrap().type_(), ColumnType::String);
+
+        // an empty table keeps its columns
+        assert_eq!(s.get("orders").unwrap().column_search("total").unwrap().type_(), ColumnType::String);
+
+        // types can still be declared
+        let mut options = LoadOptions::default();
+        options.types = parse_types("active:boolean").unwrap();
+        s.attach("tests/data/app.db", Some("typed"), &options).unwrap();
+        assert_eq!(s.get("typed_customers").unwrap().column_search("active").unwrap().type_(), ColumnType::Boolean);
+    }
+
+    #[test]
+    fn test_load_sheets() {
+        for path in ["tests/data/budget.xlsx", "tests/data/budget.ods"] {
+            let mut s = Store::new();
+
+            assert_eq!(s.load(path, None, &LoadOptions::default()).unwrap(), "budget_q1, budget_notes");
+
+            let q1 = s.get("budget_q1").unwrap();
+            assert_eq!(q1.len().unwrap(), 3);
+            assert_eq!(q1.column_search("amount").unwrap().type_(), ColumnType::Float);
+            assert_eq!(q1.column_search("paid").unwrap().type_(), ColumnType::Date);
+            assert_eq!(q1.column_search("approved").unwrap().type_(), ColumnType::Boolean);
+
+            let mut options = LoadOptions::default();
+            options.set_all("sheet=q1,range=A1:B3").unwrap();
+
+            assert_eq!(s.load(path, Some("rent"), &options).unwrap(), "rent");
+            assert_eq!(s.get("rent").unwrap().len().unwrap(), 2);
+            assert_eq!(s.get("rent").unwrap().num_columns(), 2);
+
+            options.set("sheet", "Q4").unwrap();
+            assert!(s.load(path, None, &options).is_err());
+        }
+    }
+
+    #[test]
+    fn test_load_partitioned() {
+        let mut s = Store::new();
+
+        assert_eq!(s.load("tests/data/events", None, &LoadOptions::default()).unwrap(), "events");
+        assert_eq!(s.load("tests/data/events/year=2024/*/*.csv", Some("recent"), &LoadOptions::default()).unwrap(), "recent");
+
+        let events = s.partitioned("events").unwrap();
+        assert_eq!(events.keys().len().unwrap(), 3);
+        assert_eq!(events.keys().column_search("month").unwrap().type_(), ColumnType::Int);
+
+        let all = events.read(&[true, true, true], |_| true).unwrap();
+        assert_eq!(all.len().unwrap(), 6);
+        assert_eq!(all.column_search("amount").unwrap().type_(), ColumnType::Float);
+
+        let may = events.read(&[false, false, true], |name| name == "id" || name == "month").unwrap();
+        assert_eq!(may.num_columns(), 2);
+        assert_eq!(may.len().unwrap(), 3);
+
+        assert_eq!(s.partitioned("recent").unwrap().keys().len().unwrap(), 2);
+        assert!(s.detach("events").is_ok());
+        assert!(s.partitioned("events").is_none());
+    }
+
+    #[test]
+    fn test_refresh_and_reload() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("sqlit_watched_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "id,name\n1,a\n2,b\n").unwrap();
+
+        let mut s = Store::new();
+        let alias = s.load(path, Some("watched"), &LoadOptions::default()).unwrap();
+        assert!(s.refresh().is_empty());
+
+        // only the column that was read is read again, from where the file ended
+        assert_eq!(s.get(&alias).unwrap().column_search("id").unwrap().len(), 2);
+        std::fs::OpenOptions::new().append(true).open(path).unwrap().write_all(b"03,c\n4,d\n").unwrap();
+
+        let refreshed = s.refresh();
+        assert_eq!(refreshed.len(), 1);
+        assert_eq!(refreshed[0].as_ref().unwrap(), "watched: 2 new rows");
+
+        let watched = s.get("watched").unwrap();
+        assert_eq!(watched.column_search("id").unwrap().len(), 4);
+        assert_eq!(watched.column_search("name").unwrap().len(), 4);
+
+        // rows that turn a column that was read into strings load the file again, so 03 is kept as written
+        std::fs::OpenOptions::new().append(true).open(path).unwrap().write_all(b"x,e\n").unwrap();
+        assert_eq!(s.refresh()[0].as_ref().unwrap(), "watched: reloaded");
+        assert_eq!(s.get("watched").unwrap().column_search("id").unwrap().to_strings()[2].as_deref(), Some("03"));
+
+        // anything but an append loads the file again
+        std::fs::write(path, "id,name\n9,z\n").unwrap();
+        assert_eq!(s.refresh()[0].as_ref().unwrap(), "watched: reloaded");
+        assert_eq!(s.get("watched").unwrap().column_search("id").unwrap().len(), 1);
+
+        std::fs::write(path, "id,name\n9,z\n8,y\n").unwrap();
+        assert_eq!(s.reload("watched").unwrap(), "watched");
+        assert_eq!(s.get("watched").unwrap().len().unwrap(), 2);
+        std::fs::remove_file(path).unwrap();
+
+        assert!(s.reload("missing").is_err());
+        assert!(s.detach("watched").is_ok());
+        assert!(s.refresh().is_empty());
+    }
+
+    #[test]
+    fn test_read_file_is_cached() {
+        let s = Store::new();
+        let mut options = LoadOptions::default();
+        options.set("sep", "tab").unwrap();
+
+        let first = s.read_file("tests/data/test.tsv", &options).unwrap();
+        let second = s.read_file("tests/data/test.tsv", &options).unwrap();
+
+        assert_eq!(first.alias(), "test");
+        assert!(std::rc::Rc::ptr_eq(&first.column("test", "a").unwrap().unwrap(), &second.column("test", "a").unwrap().unwrap()));
+        assert!(s.get("test").is_err());
+
+        // read differently, the file is read again
+        options.set("header", "false").unwrap();
+        let headless = s.read_file("tests/data/test.tsv", &options).unwrap();
+
+        assert!(headless.column("test", "a").unwrap().is_none());
+        assert_eq!(headless.len().unwrap(), first.len().unwrap() + 1);
+    }
+}
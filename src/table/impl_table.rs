@@ -1,65 +1,182 @@
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead};
+use std::io::{BufRead, IsTerminal, Read};
 use rayon::prelude::*;
 use std::path::Path;
-use crate::build_column::build_column;
+use crate::build_column::{build_typed_column, ColumnBuilder};
 use crate::result::{SqlResult, SqlError};
 use crate::result::ErrorType::{Lookup, Runtime};
-use crate::table::{Table, Column, NamedColumn, TableMeta};
-use crate::ingest::{SepFinder, read_line};
+use crate::table::{Table, Column, ColumnType, NamedColumn, TableMeta, Slot, Source};
+use crate::ingest::{LoadOptions, RecordReader, RecordBlocks, RecordSlices, Dialect, InputFormat, read_json, json_column, read_schema_file, schema_file_for, Fields,
+                    Compression, strip_compression};
+use crate::ingest::{Database, JsonFields, ColumnSpec, Cell, read_workbook, LoadReport, Reject, Encoding, Decoder};
+use crate::columnar::{read_parquet, read_arrow};
 use std::rc::Rc;
+use std::borrow::Cow;
 
-/// uses the filename minus the extension
+/// the path that reads a table from stdin
+pub const STDIN_PATH: &str = "-";
+
+/// the name given to a table read from stdin
+pub const STDIN_ALIAS: &str = "stdin";
+
+/// how much of a file is buffered up front, the dialect is detected from the lines it holds
+pub (super) const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// the raw values of each column, borrowed from the text they were read from where they can be
+type RawColumns<'a> = Vec<Vec<Option<Cow<'a, str>>>>;
+
+/// the raw values of each column of part of a file, the records that were skipped and how many were padded
+type SplitPart<'a> = (RawColumns<'a>, Vec<Reject>, usize);
+
+/// how many rows are converted at a time. Each block of a file is converted before the next is read,
+/// which bounds the raw text held at once
+const CHUNK_ROWS: usize = 64 * 1024;
+
+/// uses the filename minus the extension, and minus a compression extension, IE sales.csv.gz is sales
 fn extract_table_name(file_path: &str) -> Option<String> {
-    Path::new(file_path).file_stem()?.to_str().map(|s| {
+    Path::new(strip_compression(file_path)).file_stem()?.to_str().map(|s| {
         s.to_string().replace(".", "_")
     })
 }
 
 impl Table {
 
-    /// Reads file into table
-    pub fn from_file(file_location: &str, separator: &Box<dyn SepFinder>, null: &str) -> Result<Self, std::io::Error> {
-        let f = File::open(file_location)?;
+    /// Reads file into table, a path of - reads from stdin into a table named stdin
+    pub fn from_file(file_location: &str, options: &LoadOptions) -> Result<Self, std::io::Error> {
+        if file_location == STDIN_PATH {
+            let stdin = std::io::stdin();
+
+            if stdin.is_terminal() {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "stdin is a terminal, pipe a file in to read it as a table"));
+            }
+
+            return Self::from_reader(stdin.lock(), STDIN_ALIAS.to_string(), options);
+        }
 
         let alias = extract_table_name(file_location)
             .ok_or(std::io::Error::new(std::io::ErrorKind::InvalidData, "could not parse table name from file"))?;
 
-        let mut lines = std::io::BufReader::new(f).lines();
+        // types declared in a schema file next to the data come before the ones the user gave
+        let schema_file = schema_file_for(file_location);
 
-        let column_line = lines.next().ok_or(std::io::Error::new(std::io::ErrorKind::InvalidData, "file is empty"))??;
+        let mut options = if schema_file.is_file() {
+            let mut with_schema = options.clone();
+            with_schema.types = read_schema_file(&schema_file)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+            with_schema.types.extend(options.types.iter().cloned());
 
-        let column_names = parse_header_line(&column_line, separator);
+            with_schema
+        } else {
+            options.clone()
+        };
 
-        let column_map = create_column_map(&alias, &column_names);
+        options.format = options.format.or_else(|| InputFormat::from_path(file_location));
 
-        let mut raw_string_columns: Vec<Vec<String>> = vec![vec!(); column_names.len()];
-        let mut line_counter = 0;
+        if let Some(InputFormat::Xlsx) | Some(InputFormat::Xls) | Some(InputFormat::Ods) = options.format {
+            return first_sheet(Self::from_workbook(file_location, None, &options)?);
+        }
+
+        // a larger buffer gives dialect detection more lines to look at
+        let mut reader = std::io::BufReader::with_capacity(SAMPLE_BYTES, open_file(file_location)?);
+
+        let format = match options.format {
+            Some(format) => format,
+            None => InputFormat::detect(reader.fill_buf()?),
+        };
 
-        for maybe_line in lines {
-            let line = maybe_line?;
-            let parsed = read_line(&line, separator);
+        // settled once when the start of the file isn't utf-8, so columns read later are decoded the same way.
+        // Utf-8 is left to the decoder, which reads what turns out not to be as windows-1252
+        if let (InputFormat::Delimited | InputFormat::Json, None) = (format, options.encoding) {
+            let detected = Encoding::detect(reader.fill_buf()?);
 
-            if parsed.len() != column_names.len() {
-                println!("Parse Error: Line {}: {}, expected {} columns, got {}",
-                         line_counter, line, column_names.len(), parsed.len())
-            } else {
-                parsed.into_iter().enumerate().for_each(|(num, s)| {
-                    raw_string_columns[num].push(s);
-                });
+            if detected != Encoding::Utf8 {
+                options.encoding = Some(detected);
             }
+        }
 
-            line_counter += 1;
+        // strict checks every value up front and rejects are written as the file is read,
+        // otherwise columns are left until a query uses them
+        if format == InputFormat::Delimited && !options.strict && options.rejects.is_none() {
+            return read_header(decode(reader, &options)?, file_location, alias, &options);
+        } else if format == InputFormat::Delimited {
+            return read_file_columns(file_location, alias, &options, None, true);
         }
 
-        let columns: Vec<Column> = raw_string_columns.into_par_iter().map(|s| {
-            build_column(s, null)
-        }).collect();
+        Self::from_reader(reader, alias, &options)
+    }
 
-        Ok(Table {
-            alias, column_map, column_names, columns: columns.into_iter().map(|c| Rc::new(c)).collect()
-        })
+    /// Reads a table from any buffered source, IE a file or stdin
+    pub fn from_reader<R: BufRead>(mut reader: R, alias: String, options: &LoadOptions) -> Result<Self, std::io::Error> {
+        // peek at the start of the input to settle anything the user didn't set
+        let format = match options.format {
+            Some(format) => format,
+            None => InputFormat::detect(reader.fill_buf()?),
+        };
+
+        match format {
+            InputFormat::Parquet | InputFormat::Arrow => read_columnar(reader, alias, format),
+            InputFormat::Xlsx | InputFormat::Xls | InputFormat::Ods => {
+                let mut bytes = vec![];
+                reader.read_to_end(&mut bytes)?;
+
+                first_sheet(read_sheets(&bytes, &alias, format, options)?)
+            },
+            InputFormat::Sqlite => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput,
+                                                           "a sqlite database holds many tables, attach it with LOAD or --sqlite and query its tables by name")),
+            InputFormat::Json => typed_table(read_json(decode(reader, options)?)?, alias, options),
+            InputFormat::Delimited => {
+                let mut reader = decode(reader, options)?;
+                let sample = String::from_utf8_lossy(reader.fill_buf()?).to_string();
+                read_records(reader, &options.dialect(&sample), alias, options)
+            },
+        }
+    }
+
+    /// Reads a workbook's sheets as tables named file_sheet, or prefix_sheet when a prefix is
+    /// given. With the sheet option set only that sheet is read, otherwise sheets without values are left out.
+    pub fn from_workbook(file_location: &str, prefix: Option<&str>, options: &LoadOptions) -> Result<Vec<Self>, std::io::Error> {
+        let format = match options.format.or_else(|| InputFormat::from_path(file_location)) {
+            Some(format @ InputFormat::Xlsx) | Some(format @ InputFormat::Xls) | Some(format @ InputFormat::Ods) => format,
+            _ => InputFormat::Xlsx,
+        };
+
+        let prefix = match prefix {
+            Some(prefix) => prefix.to_string(),
+            None => extract_table_name(file_location)
+                .ok_or(std::io::Error::new(std::io::ErrorKind::InvalidData, "could not parse table name from file"))?,
+        };
+
+        read_sheets(&std::fs::read(file_location)?, &prefix, format, options)
+    }
+
+    /// Builds a table from the raw values of each column, inferring the type of any column that wasn't declared
+    fn from_fields(alias: String, (column_names, raw_string_columns): Fields, options: &LoadOptions) -> Result<Self, std::io::Error> {
+        let mut builder = TableBuilder::new(alias, column_names, options);
+        builder.push(&raw_string_columns);
+
+        builder.finish(options, true)
+    }
+
+    /// Reads a table of a database, columns take the types sqlite stored their values with
+    pub fn from_database(table: &str, database: &Database, alias: String, options: &LoadOptions) -> Result<Self, std::io::Error> {
+        let (column_names, values) = database.export(table)?;
+
+        // without rows there is nothing to infer from, the columns are strings unless declared
+        if values.first().is_none_or(|v| v.is_empty()) {
+            let mut empty = Table::new().with_new_alias(alias);
+            let declared = declared_types(options, &column_names);
+
+            for (name, column_type) in column_names.into_iter().zip(declared) {
+                let (column, _) = build_typed_column(vec![], &options.null, &column_type.unwrap_or(ColumnType::String));
+
+                empty.push(NamedColumn { name, column: Rc::new(column) }, None);
+            }
+
+            return Ok(empty);
+        }
+
+        typed_table((column_names, values), alias, options)
     }
 
     /// Union all table and return the result
@@ -90,6 +207,47 @@ impl Table {
         }
     }
 
+    /// Reads every column that hasn't been read yet, each file is read once for all of its columns
+    pub fn materialize(&self) -> SqlResult<()> {
+        let mut pending: Vec<(&Rc<Source>, Vec<usize>)> = vec![];
+
+        for slot in self.columns.iter().filter(|s| s.read().is_none()) {
+            if let Slot::File(source, index) = slot {
+                match pending.iter_mut().find(|(s, _)| Rc::ptr_eq(s, source)) {
+                    Some((_, indices)) => indices.push(*index),
+                    None => pending.push((source, vec![*index])),
+                }
+            }
+        }
+
+        for (source, indices) in pending {
+            source.fill(&indices).map_err(|e| SqlError::io_error(format!("{}: {}", source.path, e).as_str()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Keeps only the columns whose names pass keep, IE the ones a query uses, so the rest are never read.
+    /// At least one column is kept, so the table still has its rows.
+    pub fn project<F: Fn(&str) -> bool>(&self, keep: F) -> Self {
+        let mut kept: Vec<usize> = (0..self.columns.len()).filter(|&i| keep(&self.column_names[i])).collect();
+
+        if kept.is_empty() && !self.columns.is_empty() {
+            kept.push(0);
+        }
+
+        let column_map = self.column_map.iter().filter_map(|(key, index)| {
+            kept.iter().position(|i| i == index).map(|position| (key.clone(), position))
+        }).collect();
+
+        Self {
+            alias: self.alias.clone(),
+            column_names: kept.iter().map(|&i| self.column_names[i].clone()).collect(),
+            columns: kept.iter().map(|&i| self.columns[i].clone()).collect(),
+            column_map,
+        }
+    }
+
     /// Returns the table's alias
     pub fn alias(&self) -> String {
         self.alias.clone()
@@ -99,18 +257,20 @@ impl Table {
         &self.alias
     }
 
-    pub fn as_columns(&self) -> Vec<(&str, &Column)> {
+    pub fn as_columns(&self) -> SqlResult<Vec<(&str, Rc<Column>)>> {
         self.column_names.iter()
             .zip(self.columns.iter())
-            .map(|(name, c)| (name.as_ref(), c.as_ref()))
+            .map(|(name, c)| Ok((name.as_ref(), c.try_get()?)))
             .collect()
     }
 
-    pub fn column(&self, table: &str, name: &str) -> Option<Rc<Column>> {
+    pub fn column(&self, table: &str, name: &str) -> SqlResult<Option<Rc<Column>>> {
         let key = (table.to_string().to_lowercase(), name.to_string());
-        let index = self.column_map.get(&key)?.clone();
 
-        Some(self.columns[index].clone())
+        match self.column_map.get(&key) {
+            Some(&index) => self.columns[index].try_get().map(Some),
+            None => Ok(None),
+        }
     }
 
     /// Non fully-qualified column access IE SELECT a FROM table
@@ -133,37 +293,42 @@ impl Table {
         if !found_once {
             Err(SqlError::look_up_error(name, "table"))
         } else {
-            Ok(self.columns[index].clone())
+            self.columns[index].try_get()
         }
     }
 
-    pub fn into_columns(self) -> Vec<NamedColumn> {
+    pub fn into_columns(self) -> SqlResult<Vec<NamedColumn>> {
         self.columns.into_iter().zip(self.column_names.into_iter()).map(|(column, name)| {
-            NamedColumn {
-                column, name
-            }
+            Ok(NamedColumn {
+                column: column.try_get()?, name
+            })
         }).collect()
     }
 
-    pub fn len(&self) -> usize {
-        self.columns.iter().map(|c| c.len()).max().unwrap_or(0)
+    pub fn len(&self) -> SqlResult<usize> {
+        self.columns.iter().map(|c| c.try_get().map(|c| c.len())).try_fold(0, |max, len| Ok(max.max(len?)))
     }
 
-    pub fn limit(&mut self, length: usize) -> Self {
-        Self {
+    pub fn limit(&mut self, length: usize) -> SqlResult<Self> {
+        Ok(Self {
             alias: self.alias.clone(),
             column_names: self.column_names.clone(),
             columns: self.columns.iter().map(|c| {
-                Rc::new(c.limit(length))
-            }).collect(),
+                Ok(Slot::loaded(Rc::new(c.try_get()?.limit(length))))
+            }).collect::<SqlResult<Vec<Slot>>>()?,
             column_map: self.column_map.clone()
-        }
+        })
     }
 
     pub fn merge(&self, other: &Self) -> SqlResult<Self> {
+        self.materialize()?;
+        other.materialize()?;
+
         let columns = self.columns.iter().zip(other.columns.iter()).map(|(c0, c1)| {
-            c0.concat(c1).map(|c| Rc::new(c))
-        }).collect::<SqlResult<Vec<Rc<Column>>>>()?;
+            let (c0, c1) = (c0.try_get()?, c1.try_get()?);
+
+            c0.concat(&c1).map(|c| Slot::loaded(Rc::new(c)))
+        }).collect::<SqlResult<Vec<Slot>>>()?;
 
         let column_names = self.column_names.clone();
         let column_map = self.column_map.clone();
@@ -188,7 +353,8 @@ impl Table {
             columns: columns.into_iter().map(|(a, b, c, _)| {
                 (a, b, c)
             }).collect(),
-            length: self.len(),
+            // columns that weren't read yet aren't counted
+            length: self.columns.iter().filter_map(|c| c.read()).map(|c| c.len()).max().unwrap_or(0),
             alias: self.alias.clone()
         }
     }
@@ -203,87 +369,553 @@ impl Table {
         let column = column.column;
 
         self.column_names.push(name.clone());
-        self.columns.push(column);
+        self.columns.push(Slot::loaded(column));
         self.column_map.insert(
             (table_name.to_string(), name),
             self.columns.len() - 1
         );
     }
 
-    pub fn order_by(&self, order_vec: Vec<usize>) -> Self {
-        let columns: Vec<Rc<Column>> = self.columns.iter().map(|c| {
-            Rc::new(c.order(&order_vec))
-        }).collect();
+    pub fn order_by(&self, order_vec: Vec<usize>) -> SqlResult<Self> {
+        let columns = self.columns.iter().map(|c| {
+            Ok(Slot::loaded(Rc::new(c.try_get()?.order(&order_vec))))
+        }).collect::<SqlResult<Vec<Slot>>>()?;
 
-        Self {
+        Ok(Self {
             columns,
             column_names: self.column_names.clone(),
             alias: self.alias.clone(),
             column_map: self.column_map.clone(),
-        }
+        })
     }
 
-    pub fn row(&self, idx: usize) -> Option<Table> {
-        if idx > self.len() {
-            None
+    pub fn row(&self, idx: usize) -> SqlResult<Option<Table>> {
+        if idx > self.len()? {
+            Ok(None)
         } else {
-            Some(Self {
-                columns: self.columns.iter().map(|c| Rc::new(c.row(idx))).collect(),
+            Ok(Some(Self {
+                columns: self.columns.iter().map(|c| Ok(Slot::loaded(Rc::new(c.try_get()?.row(idx))))).collect::<SqlResult<Vec<Slot>>>()?,
                 alias: self.alias.clone(),
                 column_map: self.column_map.clone(),
                 column_names: self.column_names.clone()
-            })
+            }))
         }
     }
 
-    pub fn to_columns(&self) -> Vec<NamedColumn> {
+    pub fn to_columns(&self) -> SqlResult<Vec<NamedColumn>> {
         self.columns.iter().zip(self.column_names.iter()).map(|(c, name)| {
-            NamedColumn {
-                column: c.clone(),
+            Ok(NamedColumn {
+                column: c.try_get()?,
                 name: name.clone()
-            }
+            })
         }).collect()
     }
 
+    /// columns that weren't read yet are still read from their file, and only once for both tables
     pub fn with_new_alias(&self, alias: String) -> Self {
+        Self {
+            column_map: create_column_map(&alias, &self.column_names),
+            column_names: self.column_names.clone(),
+            columns: self.columns.clone(),
+            alias,
+        }
+    }
 
-        let mut empty = Self::new();
+    pub fn where_(&self, mask: &Vec<Option<bool>>) -> SqlResult<Self> {
+        let columns = self.columns.iter().map(|c| {
+            Ok(Slot::loaded(Rc::new(c.try_get()?.select(mask))))
+        }).collect::<SqlResult<Vec<Slot>>>()?;
 
-        self.columns.iter().zip(self.column_names.iter()).for_each(|(column, name)| {
-            empty.push(NamedColumn {
-                column: column.clone(), name: name.clone()
-            }, Some(alias.as_str()))
-        });
+        Ok(Self {
+            columns,
+            column_names: self.column_names.clone(),
+            alias: self.alias.clone(),
+            column_map: self.column_map.clone(),
+        })
+    }
+}
+
+/// parquet and arrow files carry their own types, so their columns are used as they are
+fn read_columnar<R: BufRead>(mut reader: R, alias: String, format: InputFormat) -> Result<Table, std::io::Error> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
 
-        empty.alias = alias;
+    let named = match format {
+        InputFormat::Parquet => read_parquet(bytes)?,
+        _ => read_arrow(bytes)?,
+    };
 
-        empty
+    let (column_names, columns): (Vec<String>, Vec<Slot>) = named.into_iter().map(|(name, column)| {
+        (name.to_lowercase().replace(".", "_").replace(" ", "_"), Slot::loaded(Rc::new(column)))
+    }).unzip();
+
+    let column_map = create_column_map(&alias, &column_names);
+
+    Ok(Table { alias, column_map, column_names, columns })
+}
+
+/// column names are lowercase with dots and spaces replaced, unnamed columns are numbered. A byte
+/// order mark left at the start of the line isn't part of the first name.
+fn parse_header_line(header_line: &str, dialect: &Dialect) -> Vec<String> {
+    header_names(dialect.split(header_line.trim_start_matches('\u{feff}')))
+}
+
+fn header_names(header: Vec<Option<String>>) -> Vec<String> {
+    header.into_iter().enumerate().map(|(num, s)| {
+        match s {
+            Some(s) if !s.is_empty() => s.to_lowercase().replace(".", "_").replace(" ", "_"),
+            _ => num.to_string(),
+        }
+    }).collect()
+}
+
+/// the sheet part of a table name, anything but letters and digits is replaced, IE Q1 Budget is q1_budget
+fn sheet_table_name(prefix: &str, sheet: &str) -> String {
+    let sheet: String = sheet.to_lowercase().chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect();
+
+    format!("{}_{}", prefix, sheet)
+}
+
+/// the sheets of a workbook as tables, see Table::from_workbook
+fn read_sheets(bytes: &[u8], prefix: &str, format: InputFormat, options: &LoadOptions) -> std::io::Result<Vec<Table>> {
+    let sheets = read_workbook(bytes, format)?;
+
+    let sheets = match &options.sheet {
+        Some(name) => {
+            // an exact match first, as sheet names may differ only in case
+            let found = sheets.iter().position(|s| &s.name == name)
+                .or_else(|| sheets.iter().position(|s| s.name.eq_ignore_ascii_case(name)))
+                .ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, format!("workbook has no sheet named {}", name)))?;
+
+            sheets.into_iter().skip(found).take(1).collect()
+        },
+        None => sheets,
+    };
+
+    sheets.iter().filter_map(|sheet| {
+        let rows = sheet.rows(options.range.as_ref());
+
+        if rows.is_empty() && options.sheet.is_none() {
+            return None;
+        }
+
+        Some(read_sheet(rows, sheet_table_name(prefix, &sheet.name), options))
+    }).collect()
+}
+
+fn first_sheet(tables: Vec<Table>) -> std::io::Result<Table> {
+    tables.into_iter().next().ok_or(std::io::Error::new(std::io::ErrorKind::InvalidData, "workbook has no sheets with values"))
+}
+
+/// reads rows of cells into a table, the first row is the header unless told otherwise. A column of
+/// date cells is read as dates, any other column is inferred from its values as text.
+fn read_sheet(rows: Vec<Vec<Option<Cell>>>, alias: String, options: &LoadOptions) -> std::io::Result<Table> {
+    let mut rows = rows.into_iter();
+    let first = rows.next().ok_or(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{} is empty", alias)))?;
+    let header = options.header.unwrap_or(true);
+
+    let column_names = match &options.columns {
+        Some(columns) => {
+            if columns.len() != first.len() {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,
+                                               format!("{} column names given but the sheet has {} columns", columns.len(), first.len())));
+            }
+
+            columns.iter().map(|c| c.name.clone()).collect()
+        },
+        None if header => header_names(first.iter().map(|c| c.as_ref().map(|c| c.to_field())).collect()),
+        None => generate_column_names(first.len()),
+    };
+
+    let data: Vec<Vec<Option<Cell>>> = match header {
+        true => rows.collect(),
+        false => std::iter::once(first).chain(rows).collect(),
+    };
+
+    let dates = column_names.iter().enumerate().filter(|(num, _)| {
+        let mut values = data.iter().filter_map(|row| row[*num].as_ref()).peekable();
+
+        values.peek().is_some() && values.all(|c| c.is_date())
+    }).map(|(_, name)| ColumnSpec { name: name.clone(), column_type: Some(ColumnType::Date) });
+
+    let mut options = options.clone();
+    options.types = dates.chain(options.types).collect();
+
+    let raw_string_columns = (0..column_names.len()).map(|num| {
+        data.iter().map(|row| row[num].as_ref().map(|c| c.to_field())).collect()
+    }).collect();
+
+    Table::from_fields(alias, (column_names, raw_string_columns), &options)
+}
+
+/// opens a file, decompressing it when its extension or first bytes say it is compressed
+pub (super) fn open_file(file_location: &str) -> std::io::Result<Box<dyn Read>> {
+    let mut f = File::open(file_location)?;
+
+    // the extension is trusted first, then the file's first bytes
+    let compression = match Compression::from_path(file_location) {
+        Some(compression) => Some(compression),
+        None => Compression::detect(&mut f)?,
+    };
+
+    Ok(match compression {
+        Some(compression) => compression.decompress(f)?,
+        None => Box::new(f),
+    })
+}
+
+/// the first line of a file as text, decompressed and decoded the way the file would be read
+pub fn first_line(file_location: &str, options: &LoadOptions) -> std::io::Result<String> {
+    let mut line = String::new();
+    decode(std::io::BufReader::new(open_file(file_location)?), options)?.read_line(&mut line)?;
+
+    Ok(line)
+}
+
+/// text decoded into utf-8 from the encoding that was given, or detected from its start
+pub (super) fn decode<R: BufRead>(reader: R, options: &LoadOptions) -> std::io::Result<std::io::BufReader<Decoder<R>>> {
+    let decoder = match options.encoding {
+        Some(encoding) => Decoder::new(reader, encoding)?,
+        None => Decoder::detecting(reader)?,
+    };
+
+    Ok(std::io::BufReader::with_capacity(SAMPLE_BYTES, decoder))
+}
+
+fn record_reader<R: BufRead>(reader: R, dialect: &Dialect) -> RecordReader<R> {
+    match dialect.widths {
+        Some(_) => RecordReader::lines(reader),
+        None => RecordReader::with_quote(reader, dialect.quote),
+    }
+}
+
+/// the names of the columns, from the first record of the file unless they were given
+fn column_names(column_line: &str, dialect: &Dialect, options: &LoadOptions) -> std::io::Result<Vec<String>> {
+    match &options.columns {
+        Some(columns) => {
+            let found = dialect.split(column_line).len();
+
+            if columns.len() != found {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,
+                                               format!("{} column names given but the file has {} columns", columns.len(), found)));
+            }
+
+            Ok(columns.iter().map(|c| c.name.clone()).collect())
+        },
+        None if dialect.header => Ok(parse_header_line(column_line, dialect)),
+        None => Ok(generate_column_names(dialect.split(column_line).len())),
+    }
+}
+
+/// registers a file by its header, its columns are read by its Source the first time a query uses them.
+/// Until then a column's type is guessed from the records in the sample.
+pub (super) fn read_header<R: BufRead>(mut reader: R, path: &str, alias: String, options: &LoadOptions) -> std::io::Result<Table> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    let sample = reader.fill_buf()?.to_vec();
+    let dialect = options.dialect(&String::from_utf8_lossy(&sample));
+
+    let column_line = record_reader(sample.as_slice(), &dialect).next()
+        .ok_or(std::io::Error::new(std::io::ErrorKind::InvalidData, "file is empty"))??;
+
+    let column_names = column_names(&column_line, &dialect, options)?;
+    let all: Vec<usize> = (0..column_names.len()).collect();
+
+    // the last record may be cut off by the end of the sample, and is left out
+    let whole = sample.iter().rposition(|&b| b == b'\n').map_or(sample.len(), |end| end + 1);
+    let text = String::from_utf8_lossy(&sample[..whole]);
+    let (mut raw_string_columns, _, _) = split_records(&text, 0, &dialect, column_names.len(), &all, options.pad_missing);
+
+    if dialect.header {
+        raw_string_columns.iter_mut().filter(|c| !c.is_empty()).for_each(|c| { c.remove(0); });
+    }
+
+    let types = declared_types(options, &column_names).into_iter().zip(raw_string_columns).map(|(declared, raw)| {
+        let mut builder = ColumnBuilder::with_formats(declared, &options.formats);
+        builder.push(&raw, &options.null);
+
+        builder.finish(&options.null).0.type_()
+    }).collect();
+
+    let source = Rc::new(Source {
+        path: path.to_string(),
+        alias: alias.clone(),
+        options: options.clone(),
+        column_names: column_names.clone(),
+        modified: std::cell::Cell::new(modified),
+        columns: std::cell::RefCell::new(vec![None; column_names.len()]),
+        types,
+        reported: std::cell::Cell::new(false),
+        lines: std::cell::Cell::new(None),
+    });
+
+    let columns = all.into_iter().map(|num| Slot::unread(source.clone(), num)).collect();
+    let column_map = create_column_map(&alias, &column_names);
+
+    Ok(Table { alias, column_map, column_names, columns })
+}
+
+/// reads lines of separated or aligned text into a table, converting a chunk of rows at a time. Input that
+/// can't be read twice, IE stdin, keeps columns a later chunk turned into strings as ColumnBuilder leaves them.
+fn read_records<R: BufRead>(reader: R, dialect: &Dialect, alias: String, options: &LoadOptions) -> std::io::Result<Table> {
+    read_record_columns(reader, dialect, alias, options, None, true).map(|(table, _)| table)
+}
+
+/// a delimited file decoded into utf-8, and the dialect found at its start
+pub (super) fn open_delimited(path: &str, options: &LoadOptions) -> std::io::Result<(impl BufRead, Dialect)> {
+    let mut reader = decode(std::io::BufReader::with_capacity(SAMPLE_BYTES, open_file(path)?), options)?;
+    let dialect = options.dialect(&String::from_utf8_lossy(reader.fill_buf()?));
+
+    Ok((reader, dialect))
+}
+
+/// reads the wanted columns of a delimited file, or all of them when None. Columns a later chunk turned
+/// into strings are read again declared as strings, so their earlier fields are kept as written.
+pub (super) fn read_file_columns(path: &str, alias: String, options: &LoadOptions, wanted: Option<&[usize]>, mut report: bool) -> std::io::Result<Table> {
+    let mut options = options.clone();
+
+    loop {
+        let (reader, dialect) = open_delimited(path, &options)?;
+        let (table, promoted) = read_record_columns(reader, &dialect, alias.clone(), &options, wanted, report)?;
+
+        if promoted.is_empty() {
+            return Ok(table);
+        }
+
+        options.types.extend(promoted.into_iter().map(|name| ColumnSpec { name, column_type: Some(ColumnType::String) }));
+        report = false;
     }
+}
+
+/// reads only the wanted columns, by position, or all of them when None, along with the names of the columns
+/// a later chunk turned into strings. Records with the wrong number of fields and values that aren't their
+/// declared type are reported when report is set, see LoadReport.
+pub (super) fn read_record_columns<R: BufRead>(reader: R, dialect: &Dialect, alias: String, options: &LoadOptions,
+                                               wanted: Option<&[usize]>, report: bool) -> std::io::Result<(Table, Vec<String>)> {
+    let mut records = record_reader(reader, dialect);
 
-    pub fn where_(&self, mask: &Vec<Option<bool>>) -> Self {
-        let columns: Vec<Rc<Column>> = self.columns.iter().map(|c| {
-            Rc::new(c.select(&mask))
+    let column_line = records.next().ok_or(std::io::Error::new(std::io::ErrorKind::InvalidData, "file is empty"))??;
+
+    let column_names = column_names(&column_line, dialect, options)?;
+    let width = column_names.len();
+
+    let wanted = match wanted {
+        Some(wanted) => wanted.to_vec(),
+        None => (0..width).collect(),
+    };
+
+    let declared = declared_types(options, &column_names);
+    let mut load_report = LoadReport::new(&alias, width, options.rejects.clone());
+    let mut builder = TableBuilder::with_types(alias, wanted.iter().map(|&num| column_names[num].clone()).collect(),
+                                               wanted.iter().map(|&num| declared[num].clone()).collect(), options);
+    // without a header the first record is data, it starts the first block
+    let mut first_record: Option<Vec<Option<String>>> = match dialect.header {
+        true => None,
+        false => {
+            let mut fields = dialect.split(&column_line);
+            Some(wanted.iter().map(|&num| fields.get_mut(num).and_then(Option::take)).collect())
+        },
+    };
+
+    // the rest of the file is read in blocks, each cut into parts at record boundaries that are split in parallel
+    let lines_before = records.line();
+    let mut blocks = RecordBlocks::new(records.into_inner(), record_quote(dialect), lines_before);
+
+    while let Some((block, parts)) = blocks.next_block(rayon::current_num_threads())? {
+        let text = std::str::from_utf8(&block).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        let parsed: Vec<SplitPart> = parts.into_par_iter().map(|part| {
+            split_records(&text[part.bytes], part.lines_before, dialect, width, &wanted, options.pad_missing)
         }).collect();
 
-        Self {
-            columns,
-            column_names: self.column_names.clone(),
-            alias: self.alias.clone(),
-            column_map: self.column_map.clone(),
+        let mut raw_string_columns: RawColumns = match first_record.take() {
+            Some(record) => record.into_iter().map(|field| vec![field.map(Cow::Owned)]).collect(),
+            None => vec![vec!(); wanted.len()],
+        };
+
+        for (columns, rejects, padded) in parsed {
+            if let (true, Some(reject)) = (options.strict, rejects.first()) {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,
+                                               format!("line {} has {} fields, expected {}: {}", reject.line, reject.fields, width, reject.record)));
+            }
+
+            if report {
+                load_report.padded += padded;
+
+                for reject in rejects {
+                    load_report.reject(reject)?;
+                }
+            }
+
+            for (all, part) in raw_string_columns.iter_mut().zip(columns) {
+                all.extend(part);
+            }
         }
+
+        // converted before the next block is read, the fields borrow from this one
+        builder.push(&raw_string_columns);
     }
+
+    if let Some(record) = first_record {
+        builder.push(&record.into_iter().map(|field| vec![field]).collect::<Vec<_>>());
+    }
+
+    if !load_report.is_empty() {
+        eprintln!("Warning: {}", load_report);
+    }
+
+    let promoted = builder.promoted();
+
+    Ok((builder.finish(options, report)?, promoted))
 }
 
-fn parse_header_line(header_line: &String, separator: &Box<dyn SepFinder>) -> Vec<String> {
-    read_line(header_line, separator).into_iter().enumerate().map(|(num, s)| {
-        if s.len() == 0 {
-            num.to_string()
+/// quotes hold records together unless the columns are found by position
+fn record_quote(dialect: &Dialect) -> Option<char> {
+    dialect.widths.as_ref().map_or(Some(dialect.quote), |_| None)
+}
+
+/// splits a part of a file into the raw values of the wanted columns, along with the records that had the wrong
+/// number of fields. With pad set records with too few fields are filled out with nulls instead. Fields borrow
+/// from the text, see Dialect::split_borrowed.
+pub (super) fn split_records<'a>(text: &'a str, lines_before: usize, dialect: &Dialect, width: usize, wanted: &[usize], pad: bool) -> SplitPart<'a> {
+    let mut records = RecordSlices::new(text, record_quote(dialect));
+
+    let mut raw_string_columns: RawColumns = vec![vec!(); wanted.len()];
+    let mut rejects = vec![];
+    let mut padded = 0;
+
+    while let Some(record) = records.next() {
+        if record.trim().is_empty() {
+            continue;
+        }
+
+        let mut parsed = dialect.split_borrowed(record);
+
+        if parsed.len() < width && pad {
+            parsed.resize(width, None);
+            padded += 1;
+        }
+
+        if parsed.len() != width {
+            rejects.push(Reject { line: lines_before + records.line(), fields: parsed.len(), record: record.to_string() });
         } else {
-            s.to_lowercase().replace(".", "_")
+            for (all, &num) in raw_string_columns.iter_mut().zip(wanted) {
+                all.push(parsed[num].take());
+            }
+        }
+    }
+
+    (raw_string_columns, rejects, padded)
+}
+
+/// converts columns of raw fields into typed columns a chunk at a time, see ColumnBuilder
+struct TableBuilder {
+    alias: String,
+    column_names: Vec<String>,
+    builders: Vec<ColumnBuilder>,
+    null: String,
+}
+
+impl TableBuilder {
+    fn new(alias: String, column_names: Vec<String>, options: &LoadOptions) -> Self {
+        let declared = declared_types(options, &column_names);
+
+        Self::with_types(alias, column_names, declared, options)
+    }
+
+    /// the types are declared by the caller, IE when only some of a file's columns are read
+    fn with_types(alias: String, column_names: Vec<String>, declared: Vec<Option<ColumnType>>, options: &LoadOptions) -> Self {
+        let builders = declared.into_iter().map(|declared| ColumnBuilder::with_formats(declared, &options.formats)).collect();
+
+        TableBuilder { alias, column_names, builders, null: options.null.clone() }
+    }
+
+    /// converts the raw values of each column a chunk of rows at a time, columns are converted in parallel
+    fn push<S: AsRef<str> + Sync>(&mut self, raw_string_columns: &[Vec<Option<S>>]) {
+        let null = self.null.as_str();
+        let rows = raw_string_columns.first().map_or(0, Vec::len);
+
+        for start in (0..rows).step_by(CHUNK_ROWS) {
+            let end = (start + CHUNK_ROWS).min(rows);
+
+            self.builders.par_iter_mut().zip(raw_string_columns.par_iter()).for_each(|(builder, s)| {
+                builder.push(&s[start..end], null);
+            });
         }
+    }
+
+    /// the names of the columns a later chunk turned into strings
+    fn promoted(&self) -> Vec<String> {
+        self.builders.iter().zip(self.column_names.iter()).filter(|(builder, _)| builder.promoted()).map(|(_, name)| name.clone()).collect()
+    }
+
+    /// values that don't match their declared type are read as null with a warning when report is set,
+    /// or fail the table when strict
+    fn finish(self, options: &LoadOptions, report: bool) -> std::io::Result<Table> {
+        let TableBuilder { alias, column_names, builders, null } = self;
+        let mut columns = vec![];
+
+        for (builder, name) in builders.into_iter().zip(column_names.iter()) {
+            let (column, mismatches) = builder.finish(&null);
+
+            if mismatches > 0 {
+                let message = format!("{}.{}: {} values are not {}", alias, name, mismatches, format!("{:?}", column.type_()).to_lowercase());
+
+                if options.strict {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, message));
+                }
+
+                if report {
+                    eprintln!("Warning: {}, they were read as null", message);
+                }
+            }
+
+            columns.push(Slot::loaded(Rc::new(column)));
+        }
+
+        let column_map = create_column_map(&alias, &column_names);
+
+        Ok(Table { alias, column_map, column_names, columns })
+    }
+}
+
+/// a table of values read with their types, from json or a database. Columns keep those types
+/// unless they were declared
+fn typed_table((column_names, values): JsonFields, alias: String, options: &LoadOptions) -> std::io::Result<Table> {
+    let declared = declared_types(options, &column_names);
+
+    let builders = values.into_iter().zip(declared).map(|(values, declared)| match declared {
+        Some(column_type) => {
+            let mut builder = ColumnBuilder::with_formats(Some(column_type), &options.formats);
+            builder.push(&values.into_iter().map(|v| v.map(|v| v.to_field())).collect::<Vec<_>>(), &options.null);
+
+            builder
+        },
+        None => ColumnBuilder::from_column(json_column(values, &options.null)),
+    }).collect();
+
+    TableBuilder { alias, column_names, builders, null: options.null.clone() }.finish(options, true)
+}
+
+/// the type each column was declared with, by position in the column list or by name in the
+/// types option, None leaves the type to be inferred
+pub (super) fn declared_types(options: &LoadOptions, column_names: &[String]) -> Vec<Option<ColumnType>> {
+    column_names.iter().enumerate().map(|(num, name)| {
+        let listed = options.columns.as_ref()
+            .and_then(|columns| columns.get(num))
+            .and_then(|c| c.column_type.clone());
+
+        // later entries win, so types given by the user override the ones from a schema file
+        listed.or_else(|| options.types.iter().rev()
+            .find(|c| &c.name == name)
+            .and_then(|c| c.column_type.clone()))
     }).collect()
 }
 
+/// names for files without a header row, c0, c1, ...
+fn generate_column_names(count: usize) -> Vec<String> {
+    (0..count).map(|num| format!("c{}", num)).collect()
+}
+
 fn create_column_map(table_name: &String, column_names: &Vec<String>) -> HashMap<(String, String), usize> {
     column_names.iter().enumerate().map(|(index, name)| {
         ((table_name.clone(), name.clone()), index)
@@ -296,19 +928,294 @@ mod test {
 
 
     use crate::table;
-    use crate::ingest::{CsvFinder, SepFinder};
+    use crate::table::Column;
+    use crate::ingest::LoadOptions;
 
 
     #[test]
     fn build_table() {
         time_test!();
 
-        let parsed_table = table::Table::from_file("tests/data/music.csv", &(Box::new(CsvFinder{}) as Box<dyn SepFinder>), "null");
+        let parsed_table = table::Table::from_file("tests/data/music.csv", &LoadOptions::default());
 
         match parsed_table {
-            Ok(t) => assert!(t.len() > 0),
+            Ok(t) => assert!(t.len().unwrap() > 0),
             Err(_) => assert!(false),
         }
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn table_from_reader() {
+        let data = "a,b\n1,2\n3,4\n".as_bytes();
+
+        let t = table::Table::from_reader(data, "piped".to_string(), &LoadOptions::default()).unwrap();
+
+        assert_eq!(t.alias(), "piped");
+        assert_eq!(t.len().unwrap(), 2);
+    }
+
+    #[test]
+    fn later_chunks_promote_columns() {
+        let mut data = String::from("id,score,code,zip\n");
+
+        for i in 0..super::CHUNK_ROWS + 10 {
+            data.push_str(&format!("{},{},{},{:05}\n", i, i % 7, i % 3, i));
+        }
+
+        data.push_str("-1,2.5,x,n/a\n");
+
+        let t = table::Table::from_reader(data.as_bytes(), "scores".to_string(), &LoadOptions::default()).unwrap();
+
+        assert_eq!(t.len().unwrap(), super::CHUNK_ROWS + 11);
+        assert!(matches!(t.column_search("id").unwrap().as_ref(), Column::Ints(_)));
+
+        match t.column_search("score").unwrap().as_ref() {
+            Column::Floats(f) => assert_eq!(f.last().cloned().flatten(), Some(2.5)),
+            _ => assert!(false),
+        }
+
+        match t.column_search("code").unwrap().as_ref() {
+            Column::Strings(s) => assert_eq!(s[1].as_deref(), Some("1")),
+            _ => assert!(false),
+        }
+
+        // stdin can't be read again, so the zeros read away while the column was ints stay away
+        match t.column_search("zip").unwrap().as_ref() {
+            Column::Strings(s) => assert_eq!(s[1].as_deref(), Some("1")),
+            _ => assert!(false),
+        }
+
+        // a file is read again with the column declared as strings, whether it is read when used or up front
+        let path = std::env::temp_dir().join(format!("sqlit_promoted_{}.csv", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+
+        let mut strict = LoadOptions::default();
+        strict.set("strict", "true").unwrap();
+
+        for options in [LoadOptions::default(), strict] {
+            let t = table::Table::from_file(path.to_str().unwrap(), &options).unwrap();
+
+            match t.column_search("zip").unwrap().as_ref() {
+                Column::Strings(s) => assert_eq!((s[1].as_deref(), s.last().unwrap().as_deref()), (Some("00001"), Some("n/a"))),
+                _ => assert!(false),
+            }
+
+            assert!(matches!(t.column_search("id").unwrap().as_ref(), Column::Ints(_)));
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn columns_are_read_when_used() {
+        let path = std::env::temp_dir().join(format!("sqlit_lazy_{}.csv", std::process::id()));
+        std::fs::write(&path, "id,name,score\n1,a,2.5\n2,b,3\n").unwrap();
+
+        let t = table::Table::from_file(path.to_str().unwrap(), &LoadOptions::default()).unwrap();
+
+        assert!(t.columns.iter().all(|c| c.read().is_none()));
+        assert_eq!(t.meta().columns[2].2, table::ColumnType::Float);
+
+        // the table in the store shares what a query reads
+        let used = t.with_new_alias("s".to_string()).project(|name| name == "name" || name == "score");
+        used.materialize().unwrap();
+
+        assert_eq!(used.num_columns(), 2);
+        assert!(t.columns[0].read().is_none());
+        assert!(matches!(t.columns[2].read().unwrap().as_ref(), Column::Floats(f) if f == &vec![Some(2.5), Some(3.0)]));
+
+        // when the file changed since, the columns read so far are read again with the new one
+        std::fs::write(&path, "id,name,score\n1,a,2.5\n2,b,3\n3,c,4\n").unwrap();
+        let file = std::fs::File::options().append(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(5)).unwrap();
+
+        assert!(matches!(t.column_search("id").unwrap().as_ref(), Column::Ints(i) if i.len() == 3));
+        assert!(matches!(t.columns[2].read().unwrap().as_ref(), Column::Floats(f) if f.len() == 3));
+        assert!(matches!(t.columns[1].read().unwrap().as_ref(), Column::Strings(s) if s.len() == 3));
+
+        // but not when its columns changed
+        let before = table::Table::from_file(path.to_str().unwrap(), &LoadOptions::default()).unwrap();
+        std::fs::write(&path, "id,label\n1,a\n").unwrap();
+        let file = std::fs::File::options().append(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10)).unwrap();
+
+        assert!(before.column_search("name").is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn appended_records_are_numbered_after_the_lines_of_the_file() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("sqlit_lines_{}.csv", std::process::id()));
+        std::fs::write(&path, "id,name\n1,a\n\n2,b,c\n3,c\n").unwrap();
+
+        // nothing was read, the lines are counted rather than taken from the columns
+        let t = table::Table::from_file(path.to_str().unwrap(), &LoadOptions::default()).unwrap();
+        let from = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"4,d\n").unwrap();
+        let to = std::fs::metadata(&path).unwrap().len();
+
+        let (t, added) = t.with_appended(from, to).unwrap().unwrap();
+        assert_eq!(added, 1);
+
+        match &t.columns[0] {
+            table::Slot::File(source, _) => assert_eq!(source.lines.get(), Some(6)),
+            _ => panic!("the table should still be read from its file"),
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn records_with_the_wrong_number_of_fields() {
+        let data = "a,b,c\n1,2,3\n4,5\n6,7,8,9\n".as_bytes();
+
+        let skipped = table::Table::from_reader(data, "bad".to_string(), &LoadOptions::default()).unwrap();
+        assert_eq!(skipped.len().unwrap(), 1);
+
+        let mut options = LoadOptions::default();
+        options.set_all("pad_missing=true").unwrap();
+
+        let padded = table::Table::from_reader(data, "bad".to_string(), &options).unwrap();
+        assert!(matches!(padded.column_search("c").unwrap().as_ref(), Column::Ints(c) if c == &vec![Some(3), None]));
+
+        options.set("strict", "true").unwrap();
+
+        let error = table::Table::from_reader(data, "bad".to_string(), &options).err().unwrap();
+        assert_eq!(error.to_string(), "line 4 has 4 fields, expected 3: 6,7,8,9");
+    }
+
+    #[test]
+    fn legacy_encodings_and_byte_order_marks() {
+        let latin = table::Table::from_reader(&b"name,city\nJos\xe9,M\xfcnchen\n"[..], "people".to_string(), &LoadOptions::default()).unwrap();
+
+        match latin.column_search("city").unwrap().as_ref() {
+            Column::Strings(s) => assert_eq!(s[0].as_deref(), Some("M\u{fc}nchen")),
+            _ => assert!(false),
+        }
+
+        let marked = table::Table::from_reader(&b"\xef\xbb\xbfid,name\n1,a\n"[..], "marked".to_string(), &LoadOptions::default()).unwrap();
+        assert!(marked.column_search("id").is_ok());
+
+        // a latin-1 name past the start the encoding was detected from
+        let late = [b"name\n".repeat(1), b"a\n".repeat(super::SAMPLE_BYTES), b"M\xfcller\n".to_vec()].concat();
+        let late = table::Table::from_reader(late.as_slice(), "late".to_string(), &LoadOptions::default()).unwrap();
+
+        match late.column_search("name").unwrap().as_ref() {
+            Column::Strings(s) => assert_eq!(s.last().unwrap().as_deref(), Some("M\u{fc}ller")),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn multi_line_quoted_fields() {
+        let data = "id,comment\r\n1,\"first line\r\nsecond, line\"\r\n2,\"\"\r\n3,\r\n".as_bytes();
+
+        let t = table::Table::from_reader(data, "comments".to_string(), &LoadOptions::default()).unwrap();
+
+        assert_eq!(t.len().unwrap(), 3);
+
+        match t.column_search("comment").unwrap().as_ref() {
+            Column::Strings(s) => {
+                assert_eq!(s[0].as_deref(), Some("first line\r\nsecond, line"));
+                assert_eq!(s[1].as_deref(), Some(""));
+                assert_eq!(s[2], None);
+            },
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn headerless_table() {
+        let mut options = LoadOptions::default();
+        options.set("sep", "tab").unwrap();
+        options.set("header", "false").unwrap();
+
+        let t = table::Table::from_file("tests/data/test.tsv", &options).unwrap();
+
+        assert_eq!(t.len().unwrap(), 2);
+        assert!(t.column_search("c0").is_ok());
+    }
+
+    #[test]
+    fn user_supplied_columns() {
+        let mut options = LoadOptions::default();
+        options.set_all("columns=id:string,score:float,header=false").unwrap();
+
+        let t = table::Table::from_reader("007,1\n008,2\n".as_bytes(), "scores".to_string(), &options).unwrap();
+
+        assert_eq!(t.len().unwrap(), 2);
+        assert_eq!(t.column_search("id").unwrap().type_(), table::ColumnType::String);
+        assert_eq!(t.column_search("score").unwrap().type_(), table::ColumnType::Float);
+
+        options.set("columns", "id").unwrap();
+        assert!(table::Table::from_reader("007,1\n".as_bytes(), "scores".to_string(), &options).is_err());
+    }
+
+    #[test]
+    fn type_overrides() {
+        let mut options = LoadOptions::default();
+        options.set("types", "zip:string,n:int").unwrap();
+
+        let t = table::Table::from_reader("zip,n\n02134,1\n10001,x\n".as_bytes(), "zips".to_string(), &options).unwrap();
+
+        assert_eq!(t.column_search("zip").unwrap().type_(), table::ColumnType::String);
+        assert_eq!(t.column_search("n").unwrap().type_(), table::ColumnType::Int);
+
+        options.set("strict", "true").unwrap();
+        assert!(table::Table::from_reader("zip,n\n02134,1\n10001,x\n".as_bytes(), "zips".to_string(), &options).is_err());
+    }
+
+    #[test]
+    fn value_formats() {
+        let data = "paid,amount,at,sent\nY,\"1.234,5\",08.12.2020 10:30,1607423400000\nn,\"3,5\",09.12.2020 08:00,1607509800000\n";
+
+        let mut options = LoadOptions::default();
+        options.set("decimal", ",").unwrap();
+        options.set("date_format", "%d.%m.%Y %H:%M").unwrap();
+        options.set_all("yes_no=true,types=sent:date,epoch=ms").unwrap();
+
+        let t = table::Table::from_reader(data.as_bytes(), "payments".to_string(), &options).unwrap();
+
+        assert_eq!(t.column_search("paid").unwrap().type_(), table::ColumnType::Boolean);
+        assert_eq!(t.column_search("amount").unwrap().type_(), table::ColumnType::Float);
+        assert_eq!(t.column_search("at").unwrap().type_(), table::ColumnType::Date);
+        assert_eq!(t.column_search("sent").unwrap().type_(), table::ColumnType::Date);
+
+        match (t.column_search("amount").unwrap().as_ref(), t.column_search("sent").unwrap().as_ref()) {
+            (table::Column::Floats(f), table::Column::Dates(d)) => {
+                assert_eq!(f, &vec![Some(1234.5), Some(3.5)]);
+                assert_eq!(d, &vec![Some(1607423400), Some(1607509800)]);
+            },
+            _ => assert!(false),
+        }
+
+        assert!(options.set("epoch", "hours").is_err());
+        assert!(options.set("date_format", "%Q").is_err());
+    }
+
+    #[test]
+    fn schema_sidecar() {
+        let t = table::Table::from_file("tests/data/zips.csv", &LoadOptions::default()).unwrap();
+
+        match t.column_search("zip").unwrap().as_ref() {
+            table::Column::Strings(s) => assert_eq!(s[0].as_deref(), Some("02134")),
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn compressed_files() {
+        for path in vec!["tests/data/sales.csv.gz", "tests/data/sales.tsv.zst", "tests/data/archive.dat"] {
+            let t = table::Table::from_file(path, &LoadOptions::default()).unwrap();
+
+            assert_eq!(t.len().unwrap(), 2);
+            assert!(t.column_search("b").is_ok());
+        }
+
+        assert_eq!(super::extract_table_name("tests/data/sales.csv.gz").as_deref(), Some("sales"));
+    }
+}
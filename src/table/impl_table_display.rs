@@ -2,10 +2,10 @@ use crate::table::{Table, Column};
 use std::io::Write;
 use std::fmt::Display;
 use std::cmp::max;
-use std::path::Path;
 use std::fs::File;
-use chrono::NaiveDateTime;
+use crate::converters::date_time;
 use crate::result::{SqlResult, SqlError};
+use crate::output::writer_for_file;
 use std::error::Error;
 
 fn item_width(dest: &mut Vec<u8>, writable: &dyn Display) -> std::io::Result<usize> {
@@ -37,7 +37,7 @@ fn find_column_width(col: &Column, name: &String) -> std::io::Result<usize> {
                 maybe_value.as_ref().map(|$value| {
                     item_width(&mut $scratch, $block).unwrap_or(0)
                 }).unwrap_or(0)
-            }).max().unwrap_or(0);
+            }).max().unwrap_or(0)
         }
     }
     let max_width_column = match col {
@@ -55,7 +55,7 @@ fn find_column_width(col: &Column, name: &String) -> std::io::Result<usize> {
 
         Column::Dates(d) => {
             find_max!(d, scratch, |timestamp| {
-                &NaiveDateTime::from_timestamp(timestamp.clone(), 0).to_string()
+                &date_time(*timestamp).map(|t| t.to_string()).unwrap_or_default()
             })
         },
 
@@ -95,13 +95,16 @@ fn write_entry(f: &mut std::fmt::Formatter, lengths: &Vec<usize>,
 
 impl std::fmt::Display for Table {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let column_print_widths = self.columns.iter().zip(self.column_names.iter()).map(|(c, s)| {
+        // a column that can't be read fails the formatting, tables are read before they are written
+        let columns = self.columns.iter().map(|c| c.try_get()).collect::<SqlResult<Vec<_>>>().map_err(|_| std::fmt::Error)?;
+
+        let column_print_widths = columns.iter().zip(self.column_names.iter()).map(|(c, s)| {
             find_column_width(c, s)
         }).collect::<std::io::Result<Vec<usize>>>().map_err(|_| {
             std::fmt::Error::default()
         })?;
 
-        let max_length = self.columns.iter().map(|c| c.len()).max().unwrap_or(0);
+        let max_length = columns.iter().map(|c| c.len()).max().unwrap_or(0);
         let mut scratch = Vec::new();
 
         for (num, name) in self.column_names.iter().enumerate() {
@@ -111,7 +114,7 @@ impl std::fmt::Display for Table {
         writeln!(f)?;
 
         for i in 0..max_length {
-            for (num, col) in self.columns.iter().enumerate() {
+            for (num, col) in columns.iter().enumerate() {
                 let mut index = i;
                 if i > col.len() {
                     index = col.len() - 1;
@@ -144,7 +147,7 @@ impl std::fmt::Display for Table {
                     Column::Dates(d) => write_entry(f,
                                                     &column_print_widths,
                                                     num,
-                                                    as_display!(d[index].map(|t| NaiveDateTime::from_timestamp(t.clone(), 0).to_string())),
+                                                    as_display!(d[index].and_then(date_time).map(|t| t.to_string())),
                                                     &mut scratch)?,
 
                     Column::Booleans(b) => write_entry(f,
@@ -164,42 +167,11 @@ impl std::fmt::Display for Table {
 }
 
 impl Table {
+    /// writes the table in the format given by the file's extension, IE .csv, .tsv, .json
     pub fn write_to_file(&self, filename: &str) -> SqlResult<()> {
-        let path = Path::new(filename);
-
         // Open a file in write-only mode, returns `io::Result<File>`
         let mut file = File::create(filename).map_err(|e| SqlError::io_error(&e.to_string()))?;
 
-        let mut separator = if filename.ends_with(".tsv") {
-            "\t"
-        } else {
-            ","
-        }.to_string();
-
-        for (i, s) in self.column_names.iter().enumerate() {
-            if i > 0 {
-                file.write(separator.as_bytes());
-            }
-            file.write(s.as_bytes());
-        }
-
-        let quote = "\"".to_string();
-
-        for i in 0..self.len() {
-
-            file.write("\n".as_bytes());
-
-            for (num, column) in self.columns.iter().enumerate() {
-                if num > 0 {
-                    file.write(separator.as_bytes());
-                }
-                file.write(quote.as_bytes());
-                column.as_writable(i, &mut file, "NULL");
-                file.write(quote.as_bytes());
-
-            }
-        }
-
-        Ok(())
+        writer_for_file(filename).write(self, &mut file).map_err(|e| SqlError::io_error(&e.to_string()))
     }
-}
\ No newline at end of file
+}
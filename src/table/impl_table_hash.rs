@@ -1,22 +1,23 @@
 use crate::table::{Column, Table};
+use crate::result::SqlResult;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hasher, Hash};
 
 impl Table {
 
-    pub fn hash_row(&self, idx: usize) -> u64 {
+    pub fn hash_row(&self, idx: usize) -> SqlResult<u64> {
         let mut hasher = DefaultHasher::new();
 
-        self.columns.iter().for_each(|column| {
-            match column.as_ref() {
+        for column in self.columns.iter() {
+            match column.try_get()?.as_ref() {
                 Column::Booleans(v) => v[idx].hash(&mut hasher),
                 Column::Ints(v) => v[idx].hash(&mut hasher),
                 Column::Floats(v) => (v[idx].map(|i| (i * 1e6).round() as i64)).hash(&mut hasher),
                 Column::Strings(v) => v[idx].hash(&mut hasher),
                 Column::Dates(v) => v[idx].hash(&mut hasher),
             }
-        });
+        }
 
-        hasher.finish()
+        Ok(hasher.finish())
     }
 }
\ No newline at end of file
@@ -3,9 +3,17 @@ mod impl_store;
 mod impl_table_display;
 mod impl_column;
 mod impl_table_hash;
+mod impl_source;
+mod impl_partitioned;
 
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::time::SystemTime;
+use crate::ingest::LoadOptions;
+
+pub use impl_table::{STDIN_PATH, STDIN_ALIAS, first_line};
+pub use impl_store::is_glob;
 
 pub type DateTime = i64;
 
@@ -33,12 +41,32 @@ pub struct NamedColumn {
     pub column: Rc<Column>
 }
 
+/// a column, or the file and position it is read from the first time it's used
+#[derive(Clone)]
+enum Slot {
+    Loaded(Rc<Column>),
+    File(Rc<Source>, usize),
+}
+
+/// a delimited file registered by its header, its columns are read when a query first uses them
+struct Source {
+    path: String,
+    alias: String,
+    options: LoadOptions,
+    column_names: Vec<String>,
+    modified: Cell<SystemTime>, // when the columns that were read were read
+    types: Vec<ColumnType>, // guessed from the start of the file, until a column is read
+    columns: RefCell<Vec<Option<Rc<Column>>>>, // by position, the ones read so far
+    reported: Cell<bool>, // records with the wrong number of fields are only reported once
+    lines: Cell<Option<usize>>, // in the file as it was last read, counted on the first append
+}
+
 #[derive(Clone)]
 pub struct Table {
     alias: String,
     column_names: Vec<String>, // list of columns names
     column_map: HashMap<(String, String), usize>, // a map of (table, column name) to indices
-    columns: Vec<Rc<Column>>, // the actual data
+    columns: Vec<Slot>, // the actual data
 }
 
 #[derive(Clone)]
@@ -48,6 +76,38 @@ pub struct TableMeta {
     pub alias: String,
 }
 
+/// a table read straight from a path inside a query, kept until the file changes or is read differently
+struct CachedFile {
+    modified: SystemTime,
+    options: LoadOptions,
+    table: Table,
+}
+
+/// files split into folders by the values of some columns, IE events/year=2024/month=05/part.csv. The
+/// values become columns, and the files are read when a query uses them, skipping the folders its
+/// where clause rules out.
+pub struct Partitioned {
+    alias: String,
+    options: LoadOptions,
+    keys: Table, // one row per partition, with a column for each key
+    files: Vec<Vec<String>>, // the files of each partition
+    header: Table, // the first file, for the names and types of the columns in the files
+}
+
+/// where a table was loaded from, so it can be loaded again when its files change
+#[derive(Clone)]
+struct Origin {
+    path: String,
+    alias: Option<String>,
+    options: LoadOptions,
+    modified: SystemTime, // the latest of its files
+    length: u64, // the bytes in its files
+    end: Vec<u8>, // the last bytes of a single file, to tell whether it was only added to
+}
+
 pub struct Store {
-    tables: HashMap <String, Table>
+    tables: HashMap <String, Table>,
+    partitioned: HashMap<String, Partitioned>,
+    files: RefCell<HashMap<String, CachedFile>>,
+    origins: HashMap<String, Origin>,
 }
@@ -8,13 +8,13 @@ pub enum TokenType {
     // x, y, TableName, column name
     Identifier,
 
-    // SELECT, FROM, WHERE, GROUP BY, LEFT JOIN, INNER JOIN, ORDER BY
+    // SELECT, FROM, WHERE, GROUP BY, LEFT JOIN, INNER JOIN, ORDER BY, LOAD, DETACH
     Keyword,
 
     // ( , )
     Separator,
 
-    // + - = != is
+    // + - = != is =>
     Operator,
 
     // 234.344 true 1 false NULL 'hello'
@@ -84,9 +84,9 @@ impl Tokenizer {
 
         let re = Regex::new(r#"(?xi)
             [\s]* #skip white spaces
-            (?P<keyword>SELECT\s|FROM\s|WHERE\s|GROUP\s+BY|LEFT\s+JOIN|INNER\s+JOIN|ORDER\s+BY|INTO\s|LIMIT\s|ASC\s|DESC\s|AS\s)
+            (?P<keyword>SELECT\s|FROM\s|WHERE\s|GROUP\s+BY|LEFT\s+JOIN|INNER\s+JOIN|ORDER\s+BY|INTO\s|LIMIT\s|ASC\s|DESC\s|AS\s|LOAD\s|DETACH\s|WITH\s)
             |
-            (?P<operator>>=|<=|[-+/*><=%]|or\s|and\s|!=|=)
+            (?P<operator>=>|>=|<=|[-+/*><=%]|or\s|and\s|!=|=)
             |
             (?P<literal>'.+?'|[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?|[0-9]+|true\s|false\s|null\s)
             |
@@ -123,10 +123,35 @@ impl Tokenizer {
     }
 }
 
+/// splits a script into statements on ';', ignoring any ';' inside a 'quoted literal'
+pub fn split_statements(script: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in script.chars() {
+        match c {
+            '\'' => {
+                quoted = !quoted;
+                current.push(c);
+            },
+            ';' if !quoted => {
+                statements.push(current.trim().to_string());
+                current.clear();
+            },
+            _ => current.push(c),
+        }
+    }
+
+    statements.push(current.trim().to_string());
+
+    statements.into_iter().filter(|s| !s.is_empty()).collect()
+}
+
 #[cfg(test)]
 mod test {
-    use crate::tokenizer::Tokenizer;
-    use crate::tokenizer::TokenType::{Identifier, Separator, Keyword, Literal};
+    use crate::tokenizer::{Tokenizer, split_statements};
+    use crate::tokenizer::TokenType::{Identifier, Separator, Keyword, Literal, Operator};
 
     #[test]
     fn tokenize_basic_select() {
@@ -178,4 +203,34 @@ mod test {
         });
     }
 
+    #[test]
+    fn load_with_options() {
+        let t = Tokenizer::new();
+
+        let mut tokens = t.tokenize("LOAD 'logs/*.csv' AS logs WITH (sep='\\t')".to_string());
+
+        [Keyword, Literal, Keyword, Identifier, Keyword, Separator, Identifier, Operator, Literal, Separator]
+            .iter().for_each(|type_| {
+            assert!(tokens.pop_front().unwrap().is_type(*type_));
+        });
+    }
+
+    #[test]
+    fn named_argument() {
+        let t = Tokenizer::new();
+
+        let mut tokens = t.tokenize("read_csv('x.tsv', sep => 'tab')".to_string());
+
+        [Identifier, Separator, Literal, Separator, Identifier, Operator, Literal, Separator]
+            .iter().for_each(|type_| {
+            assert!(tokens.pop_front().unwrap().is_type(*type_));
+        });
+    }
+
+    #[test]
+    fn split_script() {
+        let statements = split_statements("load 'a;b.csv' as a;\nselect * from a;\n\n;select 1");
+
+        assert_eq!(statements, vec!["load 'a;b.csv' as a", "select * from a", "select 1"]);
+    }
 }
\ No newline at end of file
@@ -11,8 +11,8 @@ use sqlit;
 fn eval_query(query: &str, table: &str) -> sqlit::result::SqlResult<sqlit::table::Table> {
     let input = query.to_string();
 
-    let store = sqlit::table::Store::from_paths(vec![table.to_string()],
-                                                &(Box::new(sqlit::ingest::CsvFinder{}) as Box<dyn sqlit::ingest::SepFinder>), "null").map_err(|_| {
+    let mut store = sqlit::table::Store::from_paths(vec![table.to_string()],
+                                                &sqlit::ingest::LoadOptions::default()).map_err(|_| {
         sqlit::result::SqlError::new("could not read in table", sqlit::result::ErrorType::Runtime)
     })?;
 
@@ -24,7 +24,7 @@ fn eval_query(query: &str, table: &str) -> sqlit::result::SqlResult<sqlit::table
 
     let parsed = sqlit::parser::rdp::RecursiveDescentParser::new(tokens).parse()?;
 
-    sqlit::eval::eval(parsed, &mut ops, &store)
+    sqlit::eval::eval(parsed, &mut ops, &mut store)
 }
 
 #[test]
@@ -35,9 +35,9 @@ fn test_column_is_hello() {
         Err(_e) => assert!(false),
         Ok(t) => {
 
-            assert!(t.len() > 0);
+            assert!(t.len().unwrap() > 0);
 
-            let cols = t.into_columns();
+            let cols = t.into_columns().unwrap();
 
             assert_eq!(cols.len(), 1);
 
@@ -66,9 +66,9 @@ fn test_join() {
     let t = result.unwrap();
 
     println!("{}", t);
-    assert_eq!(t.len(), 6);
+    assert_eq!(t.len().unwrap(), 6);
 
-    assert_eq!(t.to_columns().len(), 6);
+    assert_eq!(t.to_columns().unwrap().len(), 6);
 }
 
 #[test]
@@ -77,7 +77,7 @@ fn test_string_comp() {
 
     match result {
         Ok(r) => {
-            assert_eq!(r.len(), 1);
+            assert_eq!(r.len().unwrap(), 1);
         }
         Err(e) => {
             println!("{}", e);
@@ -4,7 +4,7 @@ use sqlit;
 fn eval_query(query: &str) -> sqlit::result::SqlResult<sqlit::table::Table> {
     let input = query.to_string();
 
-    let store = sqlit::table::Store::new();
+    let mut store = sqlit::table::Store::new();
     let mut ops = sqlit::ops::OpContext::new();
 
     let tokenizer = sqlit::tokenizer::Tokenizer::new();
@@ -13,7 +13,7 @@ fn eval_query(query: &str) -> sqlit::result::SqlResult<sqlit::table::Table> {
 
     let parsed = sqlit::parser::rdp::RecursiveDescentParser::new(tokens).parse()?;
 
-    sqlit::eval::eval(parsed, &mut ops, &store)
+    sqlit::eval::eval(parsed, &mut ops, &mut store)
 }
 
 #[test]
@@ -23,7 +23,7 @@ fn test_string_equality() {
     match result {
         Err(_e) => assert!(false),
         Ok(t) => {
-            let cols = t.into_columns();
+            let cols = t.into_columns().unwrap();
 
             assert_eq!(cols.len(), 1);
 
@@ -47,7 +47,7 @@ fn test_select_from_select() {
             assert!(false)
         },
         Ok(t) => {
-            let cols = t.into_columns();
+            let cols = t.into_columns().unwrap();
 
             assert_eq!(cols.len(), 2);
 
@@ -71,7 +71,7 @@ fn case_sensitive_string_comp() {
             assert!(false)
         },
         Ok(t) => {
-            let cols = t.into_columns();
+            let cols = t.into_columns().unwrap();
 
             assert_eq!(cols.len(), 1);
 
@@ -84,4 +84,129 @@ fn case_sensitive_string_comp() {
         }
     }
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn load_then_select() {
+    let mut store = sqlit::table::Store::new();
+    let mut ops = sqlit::ops::OpContext::new();
+    let tokenizer = sqlit::tokenizer::Tokenizer::new();
+
+    for query in vec!["load 'tests/data/test.tsv' as numbers with (sep = '\\t')", "select a + b from numbers"] {
+        let tokens = tokenizer.tokenize(query.to_string());
+        let parsed = sqlit::parser::rdp::RecursiveDescentParser::new(tokens).parse().unwrap();
+
+        match sqlit::eval::eval(parsed, &mut ops, &mut store) {
+            Err(e) => {
+                println!("{}", e);
+                assert!(false)
+            },
+            Ok(t) => println!("{}", t),
+        }
+    }
+
+    let result = sqlit::eval::eval(
+        sqlit::parser::rdp::RecursiveDescentParser::new(tokenizer.tokenize("select a + b from numbers".to_string())).parse().unwrap(),
+        &mut ops, &mut store).unwrap();
+
+    match result.into_columns().unwrap()[0].column.as_ref() {
+        sqlit::table::Column::Ints(i) => assert_eq!(i[0].unwrap(), 3),
+        _ => assert!(false)
+    }
+}
+
+#[test]
+fn select_from_file_path() {
+    let result = eval_query("select t.a + b from read_csv('tests/data/test.tsv', sep => '\\t') t");
+
+    match result {
+        Err(e) => {
+            println!("{}", e);
+            assert!(false)
+        },
+        Ok(t) => {
+            match t.into_columns().unwrap()[0].column.as_ref() {
+                sqlit::table::Column::Ints(i) => assert_eq!(i[0].unwrap(), 3),
+                _ => assert!(false)
+            }
+        }
+    }
+
+    assert!(eval_query("select * from 'tests/data/missing.csv'").is_err());
+}
+
+#[test]
+fn select_from_ndjson() {
+    let result = eval_query("select request_path, error from 'tests/data/logs.ndjson' where request_status = 500").unwrap();
+
+    match result.into_columns().unwrap()[1].column.as_ref() {
+        sqlit::table::Column::Strings(s) => assert_eq!(s, &vec![Some("timeout".to_string())]),
+        _ => assert!(false)
+    }
+}
+
+#[test]
+fn round_trip_through_parquet_and_arrow() {
+    for extension in vec!["parquet", "arrow"] {
+        let path = std::env::temp_dir().join(format!("sqlit_logs_{}.{}", std::process::id(), extension));
+        let path = path.to_str().unwrap();
+
+        eval_query(&format!("select * from 'tests/data/logs.ndjson' into '{}'", path)).unwrap();
+        let result = eval_query(&format!("select request_path, error from '{}' where request_status = 500", path));
+        std::fs::remove_file(path).unwrap();
+
+        match result.unwrap().into_columns().unwrap()[1].column.as_ref() {
+            sqlit::table::Column::Strings(s) => assert_eq!(s, &vec![Some("timeout".to_string())]),
+            _ => assert!(false)
+        }
+    }
+}
+
+#[test]
+fn select_from_partitioned_folder() {
+    let folder = std::env::temp_dir().join(format!("sqlit_events_{}", std::process::id()));
+
+    for (partition, rows) in vec![("year=2023/month=12", "1,click,2.5,0012\n"), ("year=2024/month=04", "3,click,4,07\n"), ("year=2024/month=05", "4,view,3,x\n5,click,1.5,9\n")] {
+        std::fs::create_dir_all(folder.join(partition)).unwrap();
+        std::fs::write(folder.join(partition).join("part.csv"), format!("id,kind,amount,code\n{}", rows)).unwrap();
+    }
+
+    let mut store = sqlit::table::Store::new();
+    let mut ops = sqlit::ops::OpContext::new();
+    let tokenizer = sqlit::tokenizer::Tokenizer::new();
+
+    let mut eval = |query: String| {
+        let parsed = sqlit::parser::rdp::RecursiveDescentParser::new(tokenizer.tokenize(query)).parse()?;
+        sqlit::eval::eval(parsed, &mut ops, &mut store)
+    };
+
+    eval(format!("load '{}' as events", folder.display())).unwrap();
+
+    // the partition left out by the where clause is never read
+    std::fs::remove_file(folder.join("year=2023/month=12/part.csv")).unwrap();
+
+    let ids = eval("select id from events where (year = 2024) and (amount > 2)".to_string());
+    let months = eval("select month from events where month = 5".to_string());
+    let codes = eval("select code from events where year = 2024".to_string());
+    let everything = eval("select id from events".to_string());
+
+    std::fs::remove_dir_all(&folder).unwrap();
+
+    match ids.unwrap().into_columns().unwrap()[0].column.as_ref() {
+        sqlit::table::Column::Ints(i) => assert_eq!(i, &vec![Some(3), Some(4)]),
+        _ => assert!(false)
+    }
+
+    match months.unwrap().into_columns().unwrap()[0].column.as_ref() {
+        sqlit::table::Column::Ints(i) => assert_eq!(i, &vec![Some(5), Some(5)]),
+        _ => assert!(false)
+    }
+
+    // a partition of ints and one of strings give strings as they were written
+    match codes.unwrap().into_columns().unwrap()[0].column.as_ref() {
+        sqlit::table::Column::Strings(s) => assert_eq!(s, &vec![Some("07".to_string()), Some("x".to_string()), Some("9".to_string())]),
+        _ => assert!(false)
+    }
+
+    assert!(everything.is_err());
+}